@@ -17,12 +17,17 @@
 #[ink_lang::contract]
 pub mod subscrypt {
     use core::convert::TryInto;
-    use ink_env::hash::{HashOutput, Sha2x256};
+    use ink_env::hash::{Blake2x256, HashOutput, Sha2x256};
     use ink_env::Error;
+    #[cfg(feature = "std")]
+    use ink_metadata::layout::Layout;
     use ink_prelude::string::String;
     use ink_prelude::vec;
     use ink_prelude::vec::Vec;
     use ink_storage::collections::HashMap;
+    use ink_storage::lazy::{Lazy, LazyCell, LazyHashMap};
+    #[cfg(feature = "std")]
+    use ink_storage::traits::{KeyPtr, StorageLayout};
     use ink_storage::traits::{PackedLayout, SpreadLayout};
     /// This struct represents a subscription record
     /// # fields:
@@ -31,6 +36,19 @@ pub mod subscrypt {
     /// * plan_index
     /// * subscription_time : this stores start time of each subscription (used in linkedList)
     /// * characteristics_values_encrypted : this is the features that user has chosen for her subscription
+    /// * key_version : version of provider `EncryptionKey` that characteristics are encrypted with,
+    /// 0 if provider had no key
+    /// * characteristics_version : number of times that characteristics are updated by `update_characteristics`
+    /// * schema_version : version of plan `CharacteristicSchema` that characteristics are ordered by
+    /// * periods : number of plan durations that are covered by this record, `plan` stores the
+    /// duration and price of all of them
+    /// * renewal_plan : configs of one period of the plan when the record is created, which are
+    /// kept on `renew` while the plan is only edited with grandfathering
+    /// * plan_epoch : `RenewalTerms.epoch` of the plan when the record is created
+    /// * bundle_index : index of the `Bundle` that this record is subscribed with, records of a
+    /// bundle subscription are refunded together
+    /// * complimentary : whether the record is granted by the provider without payment, these
+    /// records can not be refunded or renewed
     /// * refunded
     #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -41,23 +59,33 @@ pub mod subscrypt {
         subscription_time: u64,
         characteristics_values_encrypted: Vec<String>,
         //encrypted Data with public key of provider
+        pub key_version: u32,
+        pub characteristics_version: u32,
+        pub schema_version: u32,
+        pub periods: u64,
+        pub renewal_plan: PlanConsts,
+        pub plan_epoch: u32,
+        pub bundle_index: Option<u128>,
+        pub complimentary: bool,
         pub refunded: bool,
     }
 
     /// This struct stores user plan records
     /// # fields:
-    /// * subscription_records
+    /// * record_count : number of `SubscriptionRecord` of the user for the provider, which are
+    /// stored in `subscription_records` of `Subscrypt`
     /// * pass_hash : hash of (token + pass_phrase) for authenticating user without wallet
     #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct PlanRecord {
-        pub subscription_records: Vec<SubscriptionRecord>,
+        pub record_count: u128,
         pass_hash: [u8; 32],
     }
 
     /// This struct stores configs of plan which is set by provider
     /// # Note
     /// `max_refund_permille_policy` is out of 1000
+    /// `duration` of `Lifetime` plans is `LIFETIME_DURATION`
     #[derive(scale::Encode, scale::Decode, PackedLayout, SpreadLayout, Debug, Clone, Copy)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
     pub struct PlanConsts {
@@ -65,20 +93,33 @@ pub mod subscrypt {
         pub(crate) price: u128,
         pub(crate) max_refund_permille_policy: u128,
         pub disabled: bool,
+        pub kind: PlanKind,
+    }
+
+    /// Kind of a plan
+    /// # Note
+    /// Subscriptions of `Lifetime` plans never expire and can not be renewed, the kind of a plan
+    /// can not be changed by edits
+    #[derive(
+        scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy, PartialEq, Eq,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PlanKind {
+        Recurring,
+        Lifetime,
     }
 
     /// This struct represents a provider
     /// # fields:
-    /// * plans
+    /// * plan_count : number of plans of the provider, which are stored in `plans` and
+    /// `plans_characteristics` of `Subscrypt`
     /// * money_address : provider earned money will be sent to this address
     /// * payment_manager : struct for handling refund requests
     /// * subscrypt_pass_hash : password of provider to login into SubsCrypt Dashboard
-    /// * plans_characteristics : array of key arrays of features of that plan
     #[derive(scale::Encode, scale::Decode, PackedLayout, SpreadLayout, Debug)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct Provider {
-        pub plans: Vec<PlanConsts>,
-        pub(crate) plans_characteristics: Vec<Vec<String>>,
+        pub plan_count: u128,
         pub(crate) money_address: AccountId,
         payment_manager: LinkedList,
         pub subscrypt_pass_hash: [u8; 32],
@@ -118,42 +159,733 @@ pub mod subscrypt {
         next_day: u64,
     }
 
+    /// This struct stores the prepaid balance of a user for metered plans of a provider
+    /// # fields:
+    /// * balance : remaining amount that can be debited by usage reports
+    /// * spend_cap : maximum amount that can be debited in each `cap_period`, 0 means no cap
+    /// * cap_period : length of spend cap period in seconds
+    /// * period_start : start time of the current spend cap period
+    /// * spent_in_period : amount debited in the current spend cap period
+    /// * nonce : number of accepted usage reports, each report has to provide the next nonce
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct Escrow {
+        pub balance: u128,
+        pub spend_cap: u128,
+        pub cap_period: u64,
+        pub period_start: u64,
+        pub spent_in_period: u128,
+        pub nonce: u64,
+    }
+
+    /// This struct represents a receiver of a share of provider payouts
+    /// # fields:
+    /// * address
+    /// * share_permille : share of each payout which is out of 1000
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct Payee {
+        pub address: AccountId,
+        pub share_permille: u128,
+    }
+
+    /// This struct represents an affiliate of a provider
+    /// # fields:
+    /// * commission_permille : share of provider portion of each referred subscription which is out of 1000
+    /// * earned : total commissions of the affiliate after claw backs of refunds
+    /// * withdrawable : commissions that are not withdrawn yet
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct Affiliate {
+        pub commission_permille: u128,
+        pub earned: u128,
+        pub withdrawable: u128,
+    }
+
+    /// This struct stores the affiliate that referred a subscription and its paid commission
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    struct Referral {
+        affiliate: AccountId,
+        commission: u128,
+    }
+
+    /// Algorithms that providers can use for encryption of characteristics
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub enum KeyAlgorithm {
+        X25519,
+        Sr25519,
+        Ed25519,
+    }
+
+    /// This struct represents the public key that users encrypt characteristics of a provider with
+    /// # fields:
+    /// * algorithm
+    /// * public_key
+    /// * version : starts from 1 and increases on each key rotation
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct EncryptionKey {
+        pub algorithm: KeyAlgorithm,
+        pub public_key: Vec<u8>,
+        pub version: u32,
+    }
+
+    /// Type of a plan characteristic
+    /// # Note
+    /// Values of `Enum` characteristics are not encrypted, they are the index of selected option.
+    /// Formats of `Email` and `Number` values are checked only if the provider has no
+    /// `EncryptionKey`, because otherwise they are ciphertexts.
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub enum CharacteristicType {
+        Text,
+        Email,
+        Number,
+        Enum(Vec<String>),
+    }
+
+    /// This struct represents the type and constraints of a plan characteristic
+    /// # fields:
+    /// * kind
+    /// * required : value can not be empty if it is true
+    /// * max_size : maximum length of value (ciphertext), 0 means no limit
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct CharacteristicSpec {
+        pub kind: CharacteristicType,
+        pub required: bool,
+        pub max_size: u32,
+    }
+
+    /// This struct stores the typed schema of characteristics of a plan
+    /// # fields:
+    /// * specs : `CharacteristicSpec` of each key of `plans_characteristics`, empty if plan is not typed
+    /// * version : increases on each change of characteristics of plan
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Default)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct CharacteristicSchema {
+        pub specs: Vec<CharacteristicSpec>,
+        pub version: u32,
+    }
+
+    /// Visibility of a plan
+    /// # Note
+    /// `Unlisted` and `InviteOnly` plans are not returned by `get_provider_catalog`
+    #[derive(
+        scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy, PartialEq, Eq,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Visibility {
+        Public,
+        Unlisted,
+        InviteOnly,
+    }
+
+    /// This enum represents an account in the allowlist of a plan
+    /// # Note
+    /// `Proven` entries are only valid while the merkle root that they are proven with is the
+    /// root of the plan, `Removed` entries block proofs of the merkle root that was set at the
+    /// time of removal
+    #[derive(
+        scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy, PartialEq, Eq,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    enum AllowlistEntry {
+        Added,
+        Proven([u8; 32]),
+        Removed([u8; 32]),
+    }
+
+    /// This struct stores the descriptive data of a plan
+    /// # fields:
+    /// * name
+    /// * description
+    /// * uri : optional link to rich details of the plan
+    /// * content_hash : optional hash of the rich details of the plan
+    /// * visibility
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct PlanMetadata {
+        pub name: String,
+        pub description: String,
+        pub uri: Option<String>,
+        pub content_hash: Option<[u8; 32]>,
+        pub visibility: Visibility,
+    }
+
+    /// This struct represents a plan in the result of `get_provider_catalog`
+    #[derive(scale::Encode, scale::Decode, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct CatalogPlan {
+        pub plan_index: u128,
+        pub plan: PlanConsts,
+        pub metadata: PlanMetadata,
+        pub characteristics: Vec<String>,
+    }
+
+    /// This struct represents the public profile of a provider
+    /// # fields:
+    /// * display_name
+    /// * description
+    /// * website
+    /// * logo_hash : optional hash of the logo image
+    /// * category : category that is used for filtering in `list_providers`
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Default)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct ProviderProfile {
+        pub display_name: String,
+        pub description: String,
+        pub website: String,
+        pub logo_hash: Option<[u8; 32]>,
+        pub category: String,
+    }
+
+    /// This struct represents a provider in the result of `list_providers`
+    #[derive(scale::Encode, scale::Decode, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ProviderListing {
+        pub registration_index: u32,
+        pub provider: AccountId,
+        pub username: String,
+        pub profile: ProviderProfile,
+    }
+
+    /// This struct represents a page of `list_providers`
+    /// # fields:
+    /// * listings : providers of the page
+    /// * next_start : registration index that the next page starts from, `None` if all providers
+    /// are scanned
+    #[derive(scale::Encode, scale::Decode, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ProviderPage {
+        pub listings: Vec<ProviderListing>,
+        pub next_start: Option<u32>,
+    }
+
+    /// This struct stores the capacity limit of a plan
+    /// # fields:
+    /// * max_active : maximum number of concurrent active subscriptions, 0 means no limit
+    /// * waitlist_enabled : if it is true, users can join the waitlist of the full plan by `join_waitlist`
+    /// * claim_window : seconds that a waitlisted user can subscribe after a place is freed for her
+    #[derive(
+        scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy, Default,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct PlanCapacity {
+        pub max_active: u128,
+        pub waitlist_enabled: bool,
+        pub claim_window: u64,
+    }
+
+    /// This struct stores the number of active subscriptions of a plan with limited capacity
+    /// # Description
+    ///
+    /// Like `LinkedList`, subscriptions are grouped by the day of their end time, `count` includes
+    /// the subscriptions that end on `processed_day` or later. Subscriptions that end on the
+    /// previous days are subtracted when the count is updated, at most `MAX_EXPIRY_DAYS` days by
+    /// each update.
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    struct ActiveCount {
+        count: u128,
+        processed_day: u64,
+    }
+
+    /// This struct represents the right of a waitlisted user to subscribe until `deadline`
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    struct WaitlistOffer {
+        user: AccountId,
+        deadline: u64,
+    }
+
+    /// This struct stores an edit of a plan that will be applied at `effective_time`
+    /// # fields:
+    /// * plan : new configs of plan
+    /// * effective_time
+    /// * grandfather : if it is true, existing subscribers keep renewing with their previous configs
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct ScheduledEdit {
+        pub plan: PlanConsts,
+        pub effective_time: u64,
+        pub grandfather: bool,
+    }
+
+    /// This struct stores the configs that existing subscribers of a plan move to at their next `renew`
+    /// # fields:
+    /// * epoch : number of applied edits of the plan that were not grandfathering
+    /// * plan : configs of the last of these edits
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    struct RenewalTerms {
+        epoch: u32,
+        plan: PlanConsts,
+    }
+
+    /// This struct represents a provider in the storage layout of version 1
+    /// # Note
+    /// This struct is frozen, it is only used by `migrate_storage` to rewrite old values as `Provider`
+    #[derive(scale::Encode, scale::Decode, PackedLayout, SpreadLayout, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ProviderV1 {
+        pub plans: Vec<PlanConstsV1>,
+        pub plans_characteristics: Vec<Vec<String>>,
+        pub money_address: AccountId,
+        pub payment_manager: LinkedList,
+        pub subscrypt_pass_hash: [u8; 32],
+    }
+
+    /// This struct stores configs of plan in the storage layout of version 1, all of these plans
+    /// are `Recurring`
+    #[derive(scale::Encode, scale::Decode, PackedLayout, SpreadLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PlanConstsV1 {
+        pub duration: u64,
+        pub price: u128,
+        pub max_refund_permille_policy: u128,
+        pub disabled: bool,
+    }
+
+    /// This struct represents a subscription record in the storage layout of version 1
+    /// # Note
+    /// This struct is frozen, it is only used by `migrate_storage` to rewrite old values as
+    /// `SubscriptionRecord`
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct SubscriptionRecordV1 {
+        pub provider: AccountId,
+        pub plan: PlanConstsV1,
+        pub plan_index: u128,
+        pub subscription_time: u64,
+        pub characteristics_values_encrypted: Vec<String>,
+        pub refunded: bool,
+    }
+
+    /// This struct stores user plan records in the storage layout of version 1
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct PlanRecordV1 {
+        pub subscription_records: Vec<SubscriptionRecordV1>,
+        pub pass_hash: [u8; 32],
+    }
+
+    /// Duration of `Lifetime` plans, `Recurring` plans can not have this duration
+    pub const LIFETIME_DURATION: u64 = u64::MAX;
+
+    /// Maximum number of subscriptions that can be imported by one `import_subscriptions` call
+    pub const MAX_IMPORT_BATCH_SIZE: usize = 50;
+
+    /// Maximum number of periods that can be subscribed by one `subscribe_periods` call, which
+    /// bounds the refund locks that are added for them
+    pub const MAX_PERIODS: u64 = 36;
+
+    /// Maximum number of providers that are scanned by one `list_providers` call, so filtering
+    /// by a rare category can not make a call too expensive
+    pub const MAX_PROVIDER_SCAN: u32 = 100;
+
+    /// Maximum number of days whose expired subscriptions are subtracted from the active count of
+    /// a plan by one call, so a plan that is not used for a long time can not make a call too
+    /// expensive
+    pub const MAX_EXPIRY_DAYS: u64 = 31;
+
+    /// Version of the storage layout of this code, `migrate_storage` rewrites the values of older
+    /// layouts to this version
+    pub const STORAGE_VERSION: u32 = 2;
+
+    /// This struct represents a subscription that a provider imports from another billing system
+    /// # fields:
+    /// * user
+    /// * plan_index
+    /// * start_time
+    /// * end_time
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct ImportedSubscription {
+        pub user: AccountId,
+        pub plan_index: u128,
+        pub start_time: u64,
+        pub end_time: u64,
+    }
+
+    /// This struct represents a discount of a plan for subscribing to multiple periods at once
+    /// # fields:
+    /// * min_periods : minimum number of periods that the discount is applied to
+    /// * discount_permille : discount out of 1000
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct DiscountTier {
+        pub min_periods: u64,
+        pub discount_permille: u128,
+    }
+
+    /// This struct represents a plan of a provider which is a part of a `Bundle`
+    /// # fields:
+    /// * provider
+    /// * plan_index
+    /// * share_permille : share of the bundle price that is paid to `provider` which is out of 1000
+    /// * accepted : whether `provider` accepted to take part in the bundle
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct BundlePlan {
+        pub provider: AccountId,
+        pub plan_index: u128,
+        pub share_permille: u128,
+        pub accepted: bool,
+    }
+
+    /// This struct represents plans of partner providers which are sold together at one price
+    /// # fields:
+    /// * creator : provider that created the bundle
+    /// * plans
+    /// * duration
+    /// * price
+    /// * max_refund_permille_policy : refund policy of the bundle which is out of 1000
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct Bundle {
+        pub creator: AccountId,
+        pub plans: Vec<BundlePlan>,
+        pub duration: u64,
+        pub price: u128,
+        pub max_refund_permille_policy: u128,
+    }
+
+    /// This struct represents an optional paid add-on of a plan
+    /// # fields:
+    /// * name
+    /// * price : price of the add-on for a whole duration of the plan
+    /// * disabled
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct AddOn {
+        pub name: String,
+        pub price: u128,
+        pub disabled: bool,
+    }
+
+    /// This struct stores an add-on that is purchased on top of a subscription
+    /// # fields:
+    /// * add_on_index
+    /// * record_index : index of the `SubscriptionRecord` that the add-on is attached to
+    /// * start_time
+    /// * lock_end : refundable part of the paid price is locked until this time
+    /// * price : paid price of the add-on
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    struct AddOnPurchase {
+        add_on_index: u128,
+        record_index: u128,
+        start_time: u64,
+        lock_end: u64,
+        price: u128,
+    }
+
+    /// This struct stores the subscription analytics of a plan
+    /// # fields:
+    /// * paid_subscriptions : number of subscriptions and renewals that are paid by users
+    /// * complimentary_subscriptions : number of subscriptions that are granted by the provider
+    /// * revenue : sum of paid prices of subscriptions and renewals
+    #[derive(
+        scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy, Default,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct PlanStats {
+        pub paid_subscriptions: u128,
+        pub complimentary_subscriptions: u128,
+        pub revenue: u128,
+    }
+
+    /// State of a `Dispute`
+    #[derive(
+        scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy, PartialEq, Eq,
+    )]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum DisputeStatus {
+        Open,
+        Settled,
+        Resolved,
+        Expired,
+    }
+
+    /// This struct represents a dispute of a user on her subscription
+    /// # fields:
+    /// * record_index : index of the disputed `SubscriptionRecord`
+    /// * reason_code
+    /// * frozen_amount : locked amount of the record which is frozen until the dispute is closed
+    /// * policy_refund : refund of the record by its refund policy when the dispute is opened,
+    /// which is paid to the user if the dispute expires
+    /// * opened_at
+    /// * status
+    /// * user_amount : part of `frozen_amount` that is paid to the user when the dispute is closed
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout, Debug, Clone, Copy)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, std::cmp::PartialEq))]
+    pub struct Dispute {
+        pub record_index: u128,
+        pub reason_code: u32,
+        pub frozen_amount: u128,
+        pub policy_refund: u128,
+        pub opened_at: u64,
+        pub status: DisputeStatus,
+        pub user_amount: u128,
+    }
+
+    /// Result of checking a username with `is_username_available`
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum UsernameStatus {
+        Available,
+        Taken,
+        Cooldown,
+        TooShort,
+        TooLong,
+        InvalidCharacter,
+        Reserved,
+    }
+
     pub struct ProcessReturningData {
         withdrawing_amount: u128,
         current_linked_list_head: u64,
         reduced_length: u128,
     }
 
+    /// This struct stores the options of a subscription that is handled by `subscribe_plan`
+    /// # fields:
+    /// * referrer : affiliate of the provider that referred the subscription
+    /// * periods : number of plan durations that are paid at once
+    struct SubscribeOptions {
+        referrer: Option<AccountId>,
+        periods: u64,
+    }
+
+    /// This struct stores the platform configs that are managed by `owner`
+    /// # fields:
+    /// * owner : the account that deployed the contract and manages platform configs
+    /// * platform_fee_basis_points : platform commission of each provider payout out of 10000
+    /// * username_release_cooldown : seconds that a released username can not be claimed by any account
+    /// * username_min_length and username_max_length : allowed length of usernames
+    /// * arbiter : the account appointed by `owner` to decide the disputes
+    /// * dispute_response_window : seconds that providers have to settle a dispute before the
+    /// arbiter can decide it
+    /// * dispute_arbitration_window : seconds that the arbiter has to decide a dispute after
+    /// the response window
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+    pub struct Configs {
+        pub owner: AccountId,
+        pub platform_fee_basis_points: u128,
+        pub username_release_cooldown: u64,
+        pub username_min_length: u32,
+        pub username_max_length: u32,
+        pub arbiter: AccountId,
+        pub dispute_response_window: u64,
+        pub dispute_arbitration_window: u64,
+    }
+
+    /// Lazy mapping of the storage, each value is stored in its own cell and is loaded only when its
+    /// key is accessed
+    /// # Note
+    /// Unlike `ink_storage::collections::HashMap` the keys are not stored, so the mapping can not be
+    /// iterated and inserting or removing a key does not touch the other keys
+    #[derive(Debug, SpreadLayout)]
+    pub struct Mapping<K: Ord + scale::Encode, V: PackedLayout> {
+        values: LazyHashMap<K, V, Blake2x256>,
+    }
+
     /// Main struct of contract
     /// # fields:
     /// * `start_time` : start time of the contract which is used in `LinkedList`
     /// * `provider_register_fee`
+    /// * `legacy_providers`, `legacy_users`, `legacy_daily_locked_amounts`, `legacy_records`,
+    /// `legacy_plan_index_to_record_index`, `legacy_username_to_address` and
+    /// `legacy_address_to_username` : the hashmaps of the storage layout of version 1 which keep
+    /// their values until `migrate_storage` moves them to the mappings, unlike the other maps they
+    /// are not a `Mapping` since `migrate_storage` iterates over them
+    /// * `storage_version` : version of the storage layout, which is less than `STORAGE_VERSION`
+    /// until `migrate_storage` is finished
+    /// * `upgrade_owner` : owner of a storage of version 1, which is written by the chain when it
+    /// replaces the code of the contract and is checked by `migrate_storage`
+    /// * `configs` : the platform `Configs`
+    /// * `platform_balance` : collected platform commissions that can be withdrawn by `owner`
     /// * `providers` : the hashmap that stores providers data
+    /// * `plans` : the hashmap that stores `PlanConsts` of each plan
+    /// * `plans_characteristics` : the hashmap that stores key array of features of each plan
     /// * `users` : the hashmap that stores users data
     /// * `daily_locked_amounts` : the hashmap that stores `DailyLockedAmount` data of each day in order
     /// * `records` : the hashmap that stores user's subscription records data
+    /// * `subscription_records` : the hashmap that stores each `SubscriptionRecord` of users
     /// * `plan_index_to_record_index` : the hashmap that stores user's last `SubscriptionRecord` index
-    /// * `PlanRecord.subscription_records` for each (user, provider, plan_index)
+    /// in `subscription_records` for each (user, provider, plan_index)
     /// * `username_to_address` mapping of Usernames to Addresses
     /// * `address_to_username` mapping of Addresses to Usernames
+    /// * `plan_max_seats` : the hashmap that stores number of seats of each team plan
+    /// * `seats` : the hashmap that stores members assigned by the seat admin of a team subscription
+    /// * `seat_admins` : the hashmap that stores the seat admin of each assigned member
+    /// * `seat_locks` : the hashmap that stores the locked amount of the seats other than the
+    /// admin's own seat of each team subscription record
+    /// * `plan_unit_prices` : the hashmap that stores per unit price of each metered plan
+    /// * `meters` : the hashmap that stores the account allowed to report usage for each provider
+    /// * `meter_keys` : the hashmap that stores the compressed ECDSA public key that signs usage
+    /// reports of each provider
+    /// * `escrows` : the hashmap that stores `Escrow` of each user for each provider
+    /// * `payees` : the hashmap that stores the `Payee` list that payouts of each provider are split between
+    /// * `affiliates` : the hashmap that stores `Affiliate` data of each affiliate of each provider
+    /// * `referrals` : the hashmap that stores `Referral` of user's last `SubscriptionRecord` for each (user, provider, plan_index)
+    /// * `affiliate_debts` : the hashmap that stores claw backs of withdrawn commissions of each affiliate of each provider
+    /// * `released_usernames` : the hashmap that stores the time that each released username can be claimed again
+    /// * `username_transfers` : the hashmap that stores pending username transfers
+    /// * `reserved_usernames` : the hashmap that stores usernames reserved by `owner`
+    /// * `encryption_keys` : the hashmap that stores `EncryptionKey` of each provider
+    /// * `characteristic_schemas` : the hashmap that stores `CharacteristicSchema` of each plan
+    /// * `plan_metadata` : the hashmap that stores `PlanMetadata` of each plan
+    /// * `provider_profiles` : the hashmap that stores `ProviderProfile` of each provider
+    /// * `provider_list` : addresses of providers in order of registration
+    /// * `plan_allowlists` : the hashmap that stores `AllowlistEntry` of accounts of each invite-only plan
+    /// * `plan_merkle_roots` : the hashmap that stores merkle root of allowed accounts of each invite-only plan
+    /// * `plan_capacities` : the hashmap that stores `PlanCapacity` of each plan with limited capacity
+    /// * `active_counts` : the hashmap that stores `ActiveCount` of each plan with limited capacity
+    /// * `active_end_times` : the hashmap that stores end time of the counted subscription of each user
+    /// * `expiring_counts` : the hashmap that stores number of counted subscriptions of a plan that end on each day
+    /// * `waitlists` : the hashmap that stores the FIFO waitlist of each full plan
+    /// * `waitlist_offers` : the hashmap that stores `WaitlistOffer` list of each plan
+    /// * `scheduled_edits` : the hashmap that stores pending `ScheduledEdit` of each plan
+    /// * `renewal_terms` : the hashmap that stores `RenewalTerms` of each plan which is edited
+    /// without grandfathering
+    /// * `discount_tiers` : the hashmap that stores `DiscountTier` list of each plan
+    /// * `lifetime_refund_windows` : the hashmap that stores the refund window of each lifetime plan
+    /// * `bundles` : the hashmap that stores `Bundle`s by their index
+    /// * `bundle_count` : number of created bundles
+    /// * `add_ons` : the hashmap that stores `AddOn` list of each plan
+    /// * `add_on_purchases` : the hashmap that stores the add-ons purchased on top of the
+    /// subscriptions of each user to a plan
+    /// * `plan_stats` : the hashmap that stores `PlanStats` of each plan
+    /// * `disputes` : the hashmap that stores the last `Dispute` of each user on her subscription
+    /// to a plan
+    ///
+    /// # Note
+    /// Fields are stored in order of declaration starting from the root key, so the fields of
+    /// version 1 stay first at their original keys and new fields can only be added to the end. The
+    /// fields that are added after version 1 are loaded lazily, so the contract can still be
+    /// loaded from a storage of version 1 which has no value for them.
     #[ink(storage)]
     pub struct Subscrypt {
         start_time: u64,
         pub provider_register_fee: u128,
+        // (provider AccountId) -> provider data of version 1
+        legacy_providers: HashMap<AccountId, ProviderV1>,
+        // (user AccountId) -> user data of version 1
+        legacy_users: HashMap<AccountId, User>,
+        // (provider AccountId , day_id) -> payment admission of version 1
+        legacy_daily_locked_amounts: HashMap<(AccountId, u64), DailyLockedAmount>,
+        // (user AccountId, provider AccountId) -> records of version 1
+        legacy_records: HashMap<(AccountId, AccountId), PlanRecordV1>,
+        // (user AccountId, provider AccountId, plan_index) -> index of version 1
+        legacy_plan_index_to_record_index: HashMap<(AccountId, AccountId, u128), u128>,
+        // username -> user AccountId of version 1
+        legacy_username_to_address: HashMap<String, AccountId>,
+        // user AccountId -> username of version 1
+        legacy_address_to_username: HashMap<AccountId, String>,
+        storage_version: LazyCell<u32>,
+        upgrade_owner: LazyCell<AccountId>,
+        pub configs: Lazy<Configs>,
+        platform_balance: Lazy<u128>,
         // (provider AccountId) -> provider data
-        pub providers: HashMap<AccountId, Provider>,
+        pub providers: Mapping<AccountId, Provider>,
+        // (provider AccountId, plan_index) -> plan consts
+        pub plans: Mapping<(AccountId, u128), PlanConsts>,
+        // (provider AccountId, plan_index) -> characteristics keys
+        pub(crate) plans_characteristics: Mapping<(AccountId, u128), Vec<String>>,
         // (user AccountId) -> user data
-        pub users: HashMap<AccountId, User>,
+        pub users: Mapping<AccountId, User>,
         // (provider AccountId , day_id) -> payment admission
-        daily_locked_amounts: HashMap<(AccountId, u64), DailyLockedAmount>,
+        daily_locked_amounts: Mapping<(AccountId, u64), DailyLockedAmount>,
         // (user AccountId, provider AccountId) -> PlanRecord struct
-        pub records: HashMap<(AccountId, AccountId), PlanRecord>,
+        pub records: Mapping<(AccountId, AccountId), PlanRecord>,
+        // (user AccountId, provider AccountId, record_index) -> subscription record
+        pub subscription_records: Mapping<(AccountId, AccountId, u128), SubscriptionRecord>,
         // (user AccountId, provider AccountId, plan_index) -> index
-        plan_index_to_record_index: HashMap<(AccountId, AccountId, u128), u128>,
+        plan_index_to_record_index: Mapping<(AccountId, AccountId, u128), u128>,
         // username -> user AccountId
-        username_to_address: HashMap<String, AccountId>,
+        username_to_address: Mapping<String, AccountId>,
         // user AccountId -> username
-        address_to_username: HashMap<AccountId, String>,
+        address_to_username: Mapping<AccountId, String>,
+        // (provider AccountId, plan_index) -> max seats
+        plan_max_seats: Mapping<(AccountId, u128), u128>,
+        // (admin AccountId, provider AccountId, plan_index) -> member AccountIds
+        seats: Mapping<(AccountId, AccountId, u128), Vec<AccountId>>,
+        // (member AccountId, provider AccountId, plan_index) -> admin AccountId
+        seat_admins: Mapping<(AccountId, AccountId, u128), AccountId>,
+        // (admin AccountId, provider AccountId, record_index) -> locked amount of assigned seats
+        seat_locks: Mapping<(AccountId, AccountId, u128), u128>,
+        // (provider AccountId, plan_index) -> price of each unit
+        plan_unit_prices: Mapping<(AccountId, u128), u128>,
+        // provider AccountId -> meter AccountId
+        meters: Mapping<AccountId, AccountId>,
+        // provider AccountId -> compressed secp256k1 public key of its meter
+        meter_keys: Mapping<AccountId, Vec<u8>>,
+        // (user AccountId, provider AccountId) -> Escrow struct
+        escrows: Mapping<(AccountId, AccountId), Escrow>,
+        // provider AccountId -> payees
+        payees: Mapping<AccountId, Vec<Payee>>,
+        // (provider AccountId, affiliate AccountId) -> Affiliate struct
+        affiliates: Mapping<(AccountId, AccountId), Affiliate>,
+        // (user AccountId, provider AccountId, plan_index) -> Referral struct
+        referrals: Mapping<(AccountId, AccountId, u128), Referral>,
+        // (provider AccountId, affiliate AccountId) -> claw back that is not recovered yet
+        affiliate_debts: Mapping<(AccountId, AccountId), u128>,
+        // username -> claimable time
+        released_usernames: Mapping<String, u64>,
+        // current owner AccountId -> receiver AccountId
+        username_transfers: Mapping<AccountId, AccountId>,
+        // reserved username -> ()
+        reserved_usernames: Mapping<String, ()>,
+        // (provider AccountId) -> current encryption key
+        encryption_keys: Mapping<AccountId, EncryptionKey>,
+        // (provider AccountId, plan_index) -> schema of plan characteristics
+        characteristic_schemas: Mapping<(AccountId, u128), CharacteristicSchema>,
+        // (provider AccountId, plan_index) -> metadata of plan
+        plan_metadata: Mapping<(AccountId, u128), PlanMetadata>,
+        // (provider AccountId) -> public profile
+        provider_profiles: Mapping<AccountId, ProviderProfile>,
+        // registration index -> provider AccountId
+        provider_list: Lazy<ink_storage::collections::Vec<AccountId>>,
+        // (provider AccountId, plan_index, user AccountId) -> allowlist entry
+        plan_allowlists: Mapping<(AccountId, u128, AccountId), AllowlistEntry>,
+        // (provider AccountId, plan_index) -> merkle root of allowed accounts
+        plan_merkle_roots: Mapping<(AccountId, u128), [u8; 32]>,
+        // (provider AccountId, plan_index) -> capacity limit
+        plan_capacities: Mapping<(AccountId, u128), PlanCapacity>,
+        // (provider AccountId, plan_index) -> subscriptions that are not expired or refunded
+        active_counts: Mapping<(AccountId, u128), ActiveCount>,
+        // (provider AccountId, plan_index, user AccountId) -> end time of the counted subscription
+        active_end_times: Mapping<(AccountId, u128, AccountId), u64>,
+        // (provider AccountId, plan_index, day_id) -> number of counted subscriptions ending that day
+        expiring_counts: Mapping<(AccountId, u128, u64), u128>,
+        // (provider AccountId, plan_index) -> waitlisted users
+        waitlists: Mapping<(AccountId, u128), Vec<AccountId>>,
+        // (provider AccountId, plan_index) -> offers of freed places
+        waitlist_offers: Mapping<(AccountId, u128), Vec<WaitlistOffer>>,
+        // (provider AccountId, plan_index) -> pending edit
+        scheduled_edits: Mapping<(AccountId, u128), ScheduledEdit>,
+        // (provider AccountId, plan_index) -> configs of last edit without grandfathering
+        renewal_terms: Mapping<(AccountId, u128), RenewalTerms>,
+        // (provider AccountId, plan_index) -> discounts of multi period subscriptions
+        discount_tiers: Mapping<(AccountId, u128), Vec<DiscountTier>>,
+        // (provider AccountId, plan_index) -> seconds that a lifetime subscription can be refunded
+        lifetime_refund_windows: Mapping<(AccountId, u128), u64>,
+        // bundle_index -> Bundle
+        bundles: Mapping<u128, Bundle>,
+        bundle_count: Lazy<u128>,
+        // (provider AccountId, plan_index) -> add-ons of plan
+        add_ons: Mapping<(AccountId, u128), Vec<AddOn>>,
+        // (user AccountId, provider AccountId, plan_index) -> purchased add-ons
+        add_on_purchases: Mapping<(AccountId, AccountId, u128), Vec<AddOnPurchase>>,
+        // (provider AccountId, plan_index) -> analytics of plan
+        plan_stats: Mapping<(AccountId, u128), PlanStats>,
+        // (user AccountId, provider AccountId, plan_index) -> last dispute
+        disputes: Mapping<(AccountId, AccountId, u128), Dispute>,
     }
 
     impl Default for Subscrypt {
@@ -190,375 +922,713 @@ pub mod subscrypt {
         characteristics: Vec<String>,
     }
 
-    impl Subscrypt {
-        #[ink(constructor)]
-        pub fn new() -> Self {
-            Subscrypt::default()
-        }
+    #[ink(event)]
+    pub struct SeatAddedEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        plan_index: u128,
+        admin: AccountId,
+        member: AccountId,
+        price: u128,
+    }
 
-        #[ink(constructor)]
-        pub fn default() -> Self {
-            Self {
-                start_time: Self::env().block_timestamp(),
-                provider_register_fee: 100,
-                providers: HashMap::new(),
-                users: ink_storage::collections::HashMap::new(),
-                daily_locked_amounts: ink_storage::collections::HashMap::new(),
-                records: ink_storage::collections::HashMap::new(),
-                plan_index_to_record_index: ink_storage::collections::HashMap::new(),
-                username_to_address: ink_storage::collections::HashMap::new(),
-                address_to_username: ink_storage::collections::HashMap::new(),
-            }
-        }
+    #[ink(event)]
+    pub struct SeatRevokedEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        plan_index: u128,
+        admin: AccountId,
+        member: AccountId,
+    }
 
-        /// Registering a new `Provider` by paying the required fee amount (`provider_register_fee`)
-        ///
-        /// # Panics
-        /// if length of plan inputs are not the same
-        /// If paid amount is less than `provider_register_fee`
-        /// if same `AccountId` registered as provider previously.
-        /// if username is already been taken by someone else
-        ///
-        /// # Emits
-        /// ProviderRegisterEvent
-        /// AddPlanEvent
-        ///
-        /// # Examples
-        /// Examples of different situations in `tests/test.rs`
-        #[ink(message, payable)]
-        pub fn provider_register(
-            &mut self,
-            durations: Vec<u64>,
-            prices: Vec<u128>,
-            max_refund_permille_policies: Vec<u128>,
-            address: AccountId,
-            username: String,
-            subscrypt_pass_hash: [u8; 32],
-            plans_characteristics: Vec<Vec<String>>,
-        ) {
-            assert_eq!(prices.len(), durations.len(), "Wrong Number of Args");
-            assert_eq!(
-                max_refund_permille_policies.len(),
-                durations.len(),
-                "Wrong Number of Args"
-            );
-            assert_eq!(
-                durations.len(),
-                plans_characteristics.len(),
-                "Wrong Number of Args"
-            );
-            let caller = self.env().caller();
-            let mut address_has_not_username: bool = true;
+    #[ink(event)]
+    pub struct EscrowDepositEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        amount: u128,
+    }
 
-            match self.address_to_username.get(&caller) {
-                Some(_) => {
-                    address_has_not_username = false;
-                }
-                None => {
-                    if let Some(address) = self.username_to_address.get(&username) {
-                        assert_eq!(*address, caller, "this username is invalid!")
-                    }
-                }
+    #[ink(event)]
+    pub struct EscrowWithdrawEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct UsageReportEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        plan_index: u128,
+        user_address: AccountId,
+        units: u128,
+        amount: u128,
+        nonce: u64,
+    }
+
+    #[ink(event)]
+    pub struct ReferralEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        affiliate: AccountId,
+        user_address: AccountId,
+        commission: u128,
+    }
+
+    #[ink(event)]
+    pub struct PlatformFeeChangeEvent {
+        basis_points: u128,
+    }
+
+    #[ink(event)]
+    pub struct CodeHashChangeEvent {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct CharacteristicsUpdateEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        plan_index: u128,
+        characteristics_values_encrypted: Vec<String>,
+        version: u32,
+    }
+
+    #[ink(event)]
+    pub struct WaitlistJoinEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        plan_index: u128,
+    }
+
+    #[ink(event)]
+    pub struct WaitlistOfferEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        plan_index: u128,
+        deadline: u64,
+    }
+
+    #[ink(event)]
+    pub struct PlanEditScheduledEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        plan_index: u128,
+        duration: u64,
+        price: u128,
+        max_refund_permille_policy: u128,
+        disabled: bool,
+        effective_time: u64,
+        grandfather: bool,
+    }
+
+    #[ink(event)]
+    pub struct EncryptionKeyChangeEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        algorithm: KeyAlgorithm,
+        public_key: Vec<u8>,
+        version: u32,
+    }
+
+    #[ink(event)]
+    pub struct AddOnPurchaseEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        plan_index: u128,
+        add_on_index: u128,
+        price: u128,
+    }
+
+    #[ink(event)]
+    pub struct SubscriptionRevokeEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        plan_index: u128,
+        reason_code: u32,
+        paid: u128,
+    }
+
+    #[ink(event)]
+    pub struct DisputeEvent {
+        #[ink(topic)]
+        provider: AccountId,
+        #[ink(topic)]
+        user_address: AccountId,
+        plan_index: u128,
+        status: DisputeStatus,
+        user_amount: u128,
+    }
+
+    impl Subscrypt {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Subscrypt::default()
+        }
+
+        #[ink(constructor)]
+        pub fn default() -> Self {
+            Self {
+                start_time: Self::env().block_timestamp(),
+                provider_register_fee: 100,
+                legacy_providers: HashMap::new(),
+                legacy_users: HashMap::new(),
+                legacy_daily_locked_amounts: HashMap::new(),
+                legacy_records: HashMap::new(),
+                legacy_plan_index_to_record_index: HashMap::new(),
+                legacy_username_to_address: HashMap::new(),
+                legacy_address_to_username: HashMap::new(),
+                storage_version: LazyCell::new(Some(STORAGE_VERSION)),
+                upgrade_owner: LazyCell::new(None),
+                configs: Lazy::new(Configs::new(Self::env().caller())),
+                platform_balance: Lazy::new(0),
+                providers: Mapping::new(),
+                plans: Mapping::new(),
+                plans_characteristics: Mapping::new(),
+                users: Mapping::new(),
+                daily_locked_amounts: Mapping::new(),
+                records: Mapping::new(),
+                subscription_records: Mapping::new(),
+                plan_index_to_record_index: Mapping::new(),
+                username_to_address: Mapping::new(),
+                address_to_username: Mapping::new(),
+                plan_max_seats: Mapping::new(),
+                seats: Mapping::new(),
+                seat_admins: Mapping::new(),
+                seat_locks: Mapping::new(),
+                plan_unit_prices: Mapping::new(),
+                meters: Mapping::new(),
+                meter_keys: Mapping::new(),
+                escrows: Mapping::new(),
+                payees: Mapping::new(),
+                affiliates: Mapping::new(),
+                referrals: Mapping::new(),
+                affiliate_debts: Mapping::new(),
+                released_usernames: Mapping::new(),
+                username_transfers: Mapping::new(),
+                reserved_usernames: Mapping::new(),
+                encryption_keys: Mapping::new(),
+                characteristic_schemas: Mapping::new(),
+                plan_metadata: Mapping::new(),
+                provider_profiles: Mapping::new(),
+                provider_list: Lazy::new(ink_storage::collections::Vec::new()),
+                plan_allowlists: Mapping::new(),
+                plan_merkle_roots: Mapping::new(),
+                plan_capacities: Mapping::new(),
+                active_counts: Mapping::new(),
+                active_end_times: Mapping::new(),
+                expiring_counts: Mapping::new(),
+                waitlists: Mapping::new(),
+                waitlist_offers: Mapping::new(),
+                scheduled_edits: Mapping::new(),
+                renewal_terms: Mapping::new(),
+                discount_tiers: Mapping::new(),
+                lifetime_refund_windows: Mapping::new(),
+                bundles: Mapping::new(),
+                bundle_count: Lazy::new(0),
+                add_ons: Mapping::new(),
+                add_on_purchases: Mapping::new(),
+                plan_stats: Mapping::new(),
+                disputes: Mapping::new(),
             }
+        }
 
-            assert!(
-                self.env().transferred_balance() >= self.provider_register_fee,
-                "You have to pay a minimum amount to register in the contract!"
+        /// Setting the platform commission which is deducted from every payout to providers
+        ///
+        /// # Panics
+        /// If `caller` is not `owner`
+        /// If `basis_points` is bigger than 10000
+        ///
+        /// # Emits
+        /// PlatformFeeChangeEvent
+        ///
+        /// # Examples
+        /// Examples in `platform_fee_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_platform_fee(&mut self, basis_points: u128) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
             );
             assert!(
-                !self.providers.contains_key(&caller),
-                "You can not register again in the contract!"
+                basis_points <= 10000,
+                "Platform fee can not be more than 10000"
             );
+            self.configs.platform_fee_basis_points = basis_points;
+            self.env()
+                .emit_event(PlatformFeeChangeEvent { basis_points });
+        }
 
-            if address_has_not_username {
-                self.address_to_username.insert(caller, username.clone());
-                self.username_to_address.insert(username, caller);
+        /// `owner` can use this function to collect the platform commissions
+        ///
+        /// # Returns
+        /// `paid` amount is returned
+        ///
+        /// # Panics
+        /// If `caller` is not `owner`
+        ///
+        /// # Examples
+        /// Examples in `platform_fee_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn withdraw_platform_balance(&mut self) -> u128 {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            let amount: u128 = *self.platform_balance;
+            *self.platform_balance = 0;
+            if amount > 0 {
+                assert_eq!(self.transfer(self.configs.owner, amount), Ok(()));
             }
+            amount
+        }
 
-            let provider = Provider {
-                plans: Vec::new(),
-                plans_characteristics: Vec::new(),
-                money_address: address,
-                payment_manager: LinkedList::new(),
-                subscrypt_pass_hash,
-            };
-
-            self.providers.insert(caller, provider);
-            self.add_plan(
-                durations,
-                prices,
-                max_refund_permille_policies,
-                plans_characteristics,
+        /// Setting the time that a released username can not be claimed by other accounts
+        ///
+        /// # Panics
+        /// If `caller` is not `owner`
+        ///
+        /// # Examples
+        /// Examples in `release_username_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_username_release_cooldown(&mut self, cooldown: u64) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
             );
-            self.env().emit_event(ProviderRegisterEvent { address });
+            self.configs.username_release_cooldown = cooldown;
         }
 
-        /// Add plans to `provider` storage
+        /// Appointing the account that decides the disputes which are not settled by providers
         ///
         /// # Panics
+        /// If `caller` is not `owner`
         ///
-        /// If the size of vectors passed to the method are different
-        /// If the caller is not a valid provider.
+        /// # Examples
+        /// Examples in `resolve_dispute_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_arbiter(&mut self, arbiter: AccountId) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            self.configs.arbiter = arbiter;
+        }
+
+        /// Setting the time that providers have to settle a dispute and the time that the arbiter
+        /// has to decide it after that
         ///
-        /// # Emits
-        ///  AddPlanEvent
+        /// # Panics
+        /// If `caller` is not `owner`
+        /// If one of the windows is 0
         ///
         /// # Examples
-        /// Examples in `add_plan_works` , `add_plan_fails_wrong_arguments`
+        /// Examples in `resolve_dispute_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn add_plan(
-            &mut self,
-            durations: Vec<u64>,
-            prices: Vec<u128>,
-            max_refund_permille_policies: Vec<u128>,
-            plan_characteristics: Vec<Vec<String>>,
-        ) {
-            assert_eq!(prices.len(), durations.len(), "Wrong Number of Args");
+        pub fn set_dispute_windows(&mut self, response_window: u64, arbitration_window: u64) {
             assert_eq!(
-                max_refund_permille_policies.len(),
-                durations.len(),
-                "Wrong Number of Args"
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
             );
-            assert_eq!(
-                durations.len(),
-                plan_characteristics.len(),
-                "Wrong Number of Args"
+            assert!(
+                response_window > 0 && arbitration_window > 0,
+                "Dispute windows can not be 0!"
             );
-
-            let caller = self.env().caller();
-
-            let provider = match self.providers.get_mut(&caller) {
-                Some(x) => x,
-                None => panic!("You should first register in the contract!"),
-            };
-
-            let mut index: u128 = provider.plans.len().try_into().unwrap();
-
-            for i in 0..durations.len() {
-                provider.plans.push(PlanConsts {
-                    duration: durations[i],
-                    price: prices[i],
-                    max_refund_permille_policy: max_refund_permille_policies[i],
-                    disabled: false,
-                });
-                
-                provider
-                    .plans_characteristics
-                    .push(plan_characteristics[i].clone());
-            }
-            for i in 0..durations.len() {
-                self.env().emit_event(AddPlanEvent {
-                    provider: caller,
-                    duration: durations[i],
-                    price: prices[i],
-                    index
-                });
-
-                index += 1;
-            }
+            self.configs.dispute_response_window = response_window;
+            self.configs.dispute_arbitration_window = arbitration_window;
         }
 
-        /// Editing previously created plans of the `caller`
+        /// Upgrading the code of the contract to `code_hash` while keeping its storage and balance
         ///
         /// # Note
-        ///
-        /// This will not effect the users that subscribed prior to the edition of plan
+        /// If the new code changes the storage layout, `migrate_storage` should be called until it
+        /// returns true right after the upgrade. `ink_env` 3.0.0-rc5 has no function to change the
+        /// code, so the contract imports `seal_set_code_hash` of the `seal0` module directly and
+        /// can only be deployed on a chain whose `pallet-contracts` provides it. The off-chain
+        /// environment can not change the code, so only the event is emitted there.
         ///
         /// # Panics
+        /// If `caller` is not `owner`
+        /// If `code_hash` is not uploaded to the chain
         ///
-        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// # Emits
+        /// CodeHashChangeEvent
         ///
         /// # Examples
-        /// Examples of different situations in `edit_plan_works` and `edit_plan_works2` in `tests/test.rs`
+        /// Examples in `set_code_hash_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn edit_plan(
-            &mut self,
-            plan_index: u128,
-            duration: u64,
-            price: u128,
-            max_refund_permille_policies: u128,
-            disabled: bool,
-        ) {
-            let number: usize = plan_index.try_into().unwrap();
-            let caller = self.env().caller();
-
-            let provider = match self.providers.get_mut(&caller) {
-                Some(x) => x,
-                None => panic!("You should first register in the contract!"),
-            };
-
-            let mut plan: &mut PlanConsts = match provider.plans.get_mut(number) {
-                Some(x) => x,
-                None => panic!("please select a valid plan"),
-            };
-
-            plan.duration = duration;
-            plan.price = price;
-            plan.max_refund_permille_policy = max_refund_permille_policies;
-            plan.disabled = disabled;
+        pub fn set_code_hash(&mut self, code_hash: Hash) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            #[cfg(not(feature = "std"))]
+            assert_eq!(
+                super::seal::set_code_hash(code_hash.as_ref()),
+                0,
+                "Code hash is not uploaded!"
+            );
+            self.env().emit_event(CodeHashChangeEvent { code_hash });
         }
 
-        /// Adding new characteristics to existing plans
+        /// Rewriting at most `batch_size` values of old storage layouts in the current layout,
+        /// the values of version 1 are moved in order of providers, users, locked amounts, records,
+        /// record indexes and usernames
         ///
         /// # Note
+        /// The code of version 1 has no `set_code_hash`, so a contract of version 1 only reaches
+        /// this code if the chain replaces its code, for example by a governance call. A storage of
+        /// version 1 has no owner, so the chain has to write `upgrade_owner` with the code and the
+        /// first call makes it `owner`, the other configs get the values of a new contract.
+        /// Usernames are stored in their case folded form unless that form is taken by another
+        /// account, those usernames are still found by their exact form.
         ///
-        /// This will not effect the users that subscribed prior to the edition of plan
+        /// # Returns
+        /// `true` if all of the old values are migrated and `storage_version` is set to
+        /// `STORAGE_VERSION`
         ///
         /// # Panics
-        /// If caller is not `provider`
-        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If storage is of version 1 and `upgrade_owner` is not written
+        /// If `caller` is not `owner`
+        /// If storage is already migrated
         ///
         /// # Examples
-        /// Examples of different situations in `add_characteristic_for_plan_works`
+        /// Examples in `migrate_storage_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn add_characteristic_for_plan(
-            &mut self,
-            plan_index: u128,
-            characteristics: Vec<String>,
-        ) {
-            let number: usize = plan_index.try_into().unwrap();
-            let caller = self.env().caller();
-
-            let provider = match self.providers.get_mut(&caller) {
-                Some(x) => x,
-                None => panic!("You should first register in the contract!"),
-            };
+        pub fn migrate_storage(&mut self, batch_size: u32) -> bool {
+            if self.storage_version.get().is_none() {
+                // fields that are added after version 1 have no value in its storage
+                let owner: AccountId = *self
+                    .upgrade_owner
+                    .get()
+                    .expect("Owner of the upgrade is not set!");
+                Lazy::set(&mut self.configs, Configs::new(owner));
+                Lazy::set(&mut self.platform_balance, 0);
+                Lazy::set(
+                    &mut self.provider_list,
+                    ink_storage::collections::Vec::new(),
+                );
+                Lazy::set(&mut self.bundle_count, 0);
+                self.storage_version.set(1);
+            }
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            assert!(
+                self.get_storage_version() < STORAGE_VERSION,
+                "Storage is already migrated!"
+            );
+            let mut remaining: usize = batch_size.try_into().unwrap();
 
-            let plan: &mut Vec<String> = match provider.plans_characteristics.get_mut(number) {
-                Some(x) => x,
-                None => panic!("please select a valid plan"),
-            };
+            let provider_keys: Vec<AccountId> = self
+                .legacy_providers
+                .keys()
+                .take(remaining)
+                .copied()
+                .collect();
+            remaining -= provider_keys.len();
+            for key in provider_keys {
+                let provider: ProviderV1 = self.legacy_providers.take(&key).unwrap();
+                let plan_count: u128 = provider.plans.len().try_into().unwrap();
+                for (index, (plan, characteristics)) in provider
+                    .plans
+                    .into_iter()
+                    .zip(provider.plans_characteristics.into_iter())
+                    .enumerate()
+                {
+                    let plan_index: u128 = index.try_into().unwrap();
+                    self.plans
+                        .insert((key, plan_index), Self::plan_from_v1(plan));
+                    self.plans_characteristics
+                        .insert((key, plan_index), characteristics);
+                }
+                self.providers.insert(
+                    key,
+                    Provider {
+                        plan_count,
+                        money_address: provider.money_address,
+                        payment_manager: provider.payment_manager,
+                        subscrypt_pass_hash: provider.subscrypt_pass_hash,
+                    },
+                );
+                self.provider_list.push(key);
+            }
 
-            for i in 0..characteristics.len() {
-                plan.push(characteristics[i].clone());
+            let user_keys: Vec<AccountId> =
+                self.legacy_users.keys().take(remaining).copied().collect();
+            remaining -= user_keys.len();
+            for key in user_keys {
+                let user: User = self.legacy_users.take(&key).unwrap();
+                self.users.insert(key, user);
+            }
+
+            let day_keys: Vec<(AccountId, u64)> = self
+                .legacy_daily_locked_amounts
+                .keys()
+                .take(remaining)
+                .copied()
+                .collect();
+            remaining -= day_keys.len();
+            for key in day_keys {
+                let locked: DailyLockedAmount =
+                    self.legacy_daily_locked_amounts.take(&key).unwrap();
+                self.daily_locked_amounts.insert(key, locked);
+            }
+
+            let record_keys: Vec<(AccountId, AccountId)> = self
+                .legacy_records
+                .keys()
+                .take(remaining)
+                .copied()
+                .collect();
+            remaining -= record_keys.len();
+            for key in record_keys {
+                let plan_record: PlanRecordV1 = self.legacy_records.take(&key).unwrap();
+                let record_count: u128 = plan_record.subscription_records.len().try_into().unwrap();
+                for (index, record) in plan_record.subscription_records.into_iter().enumerate() {
+                    let record_index: u128 = index.try_into().unwrap();
+                    let plan: PlanConsts = Self::plan_from_v1(record.plan);
+                    let subscription_record = SubscriptionRecord {
+                        provider: record.provider,
+                        plan,
+                        plan_index: record.plan_index,
+                        subscription_time: record.subscription_time,
+                        characteristics_values_encrypted: record.characteristics_values_encrypted,
+                        key_version: 0,
+                        characteristics_version: 0,
+                        schema_version: 0,
+                        periods: 1,
+                        renewal_plan: plan,
+                        plan_epoch: 0,
+                        bundle_index: None,
+                        complimentary: false,
+                        refunded: record.refunded,
+                    };
+                    self.subscription_records
+                        .insert((key.0, key.1, record_index), subscription_record);
+                }
+                self.records.insert(
+                    key,
+                    PlanRecord {
+                        record_count,
+                        pass_hash: plan_record.pass_hash,
+                    },
+                );
+            }
+
+            let index_keys: Vec<(AccountId, AccountId, u128)> = self
+                .legacy_plan_index_to_record_index
+                .keys()
+                .take(remaining)
+                .copied()
+                .collect();
+            remaining -= index_keys.len();
+            for key in index_keys {
+                let record_index: u128 = self.legacy_plan_index_to_record_index.take(&key).unwrap();
+                self.plan_index_to_record_index.insert(key, record_index);
+            }
+
+            let usernames: Vec<String> = self
+                .legacy_username_to_address
+                .keys()
+                .take(remaining)
+                .cloned()
+                .collect();
+            for username in usernames {
+                let address: AccountId = self.legacy_username_to_address.take(&username).unwrap();
+                self.legacy_address_to_username.take(&address);
+                let normalized: String = Self::normalize_username(&username);
+                let username: String = if self.username_to_address.contains_key(&normalized)
+                    || self.legacy_username_to_address.contains_key(&normalized)
+                {
+                    username
+                } else {
+                    normalized
+                };
+                self.username_to_address.insert(username.clone(), address);
+                self.address_to_username.insert(address, username);
+            }
+
+            if self.legacy_providers.is_empty()
+                && self.legacy_users.is_empty()
+                && self.legacy_daily_locked_amounts.is_empty()
+                && self.legacy_records.is_empty()
+                && self.legacy_plan_index_to_record_index.is_empty()
+                && self.legacy_username_to_address.is_empty()
+            {
+                self.storage_version.set(STORAGE_VERSION);
+                return true;
             }
+            false
         }
 
-        /// Disabling previously created plans of the `caller`
+        /// Returns the version of the storage layout, a storage of version 1 has no version
+        /// until `migrate_storage` is called
+        ///
+        /// # Examples
+        /// Examples in `migrate_storage_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version.get().copied().unwrap_or(1)
+        }
+
+        /// Setting the allowed length of usernames
         ///
         /// # Note
         ///
-        /// This will not effect the users that subscribed prior to the edition of plan
+        /// Usernames that are already taken are not effected.
         ///
         /// # Panics
-        ///
-        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If `caller` is not `owner`
+        /// If `min_length` is 0 or bigger than `max_length`
         ///
         /// # Examples
-        /// Examples in `change_disable_works` in `tests/test.rs`
+        /// Examples in `is_username_available_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn change_disable(&mut self, plan_index: u128) {
-            let caller = self.env().caller();
-            let number: usize = plan_index.try_into().unwrap();
-            match self.providers.get_mut(&caller) {
-                Some(provider) => {
-                    assert!(
-                        provider.plans.len() > plan_index.try_into().unwrap(),
-                        "please select a valid plan"
-                    );
-                    provider.plans[number].disabled = !provider.plans[number].disabled
-                }
-                None => panic!("You should first register in the contract!"),
-            }
+        pub fn set_username_length_limits(&mut self, min_length: u32, max_length: u32) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            assert!(
+                min_length > 0 && min_length <= max_length,
+                "Wrong username length limits"
+            );
+            self.configs.username_min_length = min_length;
+            self.configs.username_max_length = max_length;
         }
 
-        /// Subscribing to `plan_index` of the `provider_address` with `Sha2x256` hashed `pass` and array of characteristics_values_encrypted
+        /// Reserving `usernames` so they can not be claimed by anyone
         ///
-        /// In this function, we will lock (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000
-        /// in the `Linked List` of the contract and will transfer the rest of paid money directly to provider
+        /// # Panics
+        /// If `caller` is not `owner`
         ///
-        /// # Note
+        /// # Examples
+        /// Examples in `is_username_available_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn add_reserved_usernames(&mut self, usernames: Vec<String>) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            for username in usernames.iter() {
+                self.reserved_usernames
+                    .insert(Self::normalize_username(username), ());
+            }
+        }
+
+        /// Removing `usernames` from reserved usernames
         ///
-        /// The `subscrypt_pass_hash` will only be set if it's the first subscription of the `caller` to the `SubsCrypt` platform
-        /// `caller` can not subscribe to same `plan_index` of the same `provider_address` but
-        /// it can subscribe to different `plan_index` of same `provider_address` .
-        /// This line of code checks that if you previously subscribed to `provider_address` and if it's the first time
-        /// then `list_of_providers` will be updated.
-        ///  `if !self.records.contains_key(&(caller, provider_address)) `
+        /// # Panics
+        /// If `caller` is not `owner`
         ///
+        /// # Examples
+        /// Examples in `is_username_available_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn remove_reserved_usernames(&mut self, usernames: Vec<String>) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.owner,
+                "You are not the owner"
+            );
+            for username in usernames.iter() {
+                self.reserved_usernames
+                    .take(&Self::normalize_username(username));
+            }
+        }
+
+        /// Registering a new `Provider` by paying the required fee amount (`provider_register_fee`)
         ///
         /// # Panics
-        /// If paid amount is not equal to `price` of the plan
-        /// If plan is `disabled`
-        /// If `caller` is already subscribed to plan
-        /// If `provider` does not exist
-        /// If `plan_index` is bigger than the length of `plans` of `provider_address`
-        /// If 'username' has already been taken
+        /// if length of plan inputs are not the same
+        /// If paid amount is less than `provider_register_fee`
+        /// if same `AccountId` registered as provider previously.
+        /// if username is already been taken by someone else
         ///
         /// # Emits
-        /// SubscribeEvent
+        /// ProviderRegisterEvent
+        /// AddPlanEvent
         ///
         /// # Examples
-        /// Examples in `subscribe_works` and `subscribe_fails_insufficient_paying` in `tests/test.rs`
+        /// Examples of different situations in `tests/test.rs`
         #[ink(message, payable)]
-        pub fn subscribe(
+        pub fn provider_register(
             &mut self,
-            provider_address: AccountId,
-            plan_index: u128,
-            pass: [u8; 32],
+            durations: Vec<u64>,
+            prices: Vec<u128>,
+            max_refund_permille_policies: Vec<u128>,
+            address: AccountId,
             username: String,
-            characteristics_values_encrypted: Vec<String>,
+            subscrypt_pass_hash: [u8; 32],
+            plans_characteristics: Vec<Vec<String>>,
         ) {
-            let caller: AccountId = self.env().caller();
-            let time: u64 = self.env().block_timestamp();
-
-            assert!(
-                !self.check_subscription(caller, provider_address, plan_index),
-                "You are already subscribed to this plan!"
-            );
-
-            let provider = match self.providers.get(&provider_address) {
-                Some(provider) => provider,
-                None => panic!("Provider not existed in the contract!"),
-            };
-
-            let index: usize = plan_index.try_into().unwrap();
-
-            assert!(
-                provider.plans.len() > plan_index.try_into().unwrap(),
-                "Wrong plan index!"
-            );
-
-            let consts: PlanConsts = provider.plans[index];
-            let plan_characteristics: Vec<String> = provider.plans_characteristics[index].clone();
-
-            assert_eq!(
-                characteristics_values_encrypted.len(),
-                plan_characteristics.len(),
-                "invalid characteristic values!"
-            );
+            assert_eq!(prices.len(), durations.len(), "Wrong Number of Args");
             assert_eq!(
-                consts.price,
-                self.env().transferred_balance(),
-                "You have to pay exact plan price"
+                max_refund_permille_policies.len(),
+                durations.len(),
+                "Wrong Number of Args"
             );
-            assert!(!consts.disabled, "Plan is currently disabled by provider");
-            let plan_characteristics: Vec<String> = provider.plans_characteristics[index].clone();
-
             assert_eq!(
-                characteristics_values_encrypted.len(),
-                plan_characteristics.len(),
-                "invalid characteristic values!"
+                durations.len(),
+                plans_characteristics.len(),
+                "Wrong Number of Args"
             );
-
+            let caller = self.env().caller();
+            let username: String = Self::normalize_username(&username);
             let mut address_has_not_username: bool = true;
-            if self.address_to_username.contains_key(&caller) {
-                address_has_not_username = false;
-            } else {
-                match self.username_to_address.get(&username) {
-                    Some(_) => panic!("username is invalid!"),
-                    None => {}
+
+            match self.address_to_username.get(&caller) {
+                Some(_) => {
+                    address_has_not_username = false;
+                }
+                None => {
+                    if let Some(address) = self.username_address(&username) {
+                        assert_eq!(address, caller, "this username is invalid!")
+                    } else {
+                        assert_eq!(
+                            self.username_status(&username),
+                            UsernameStatus::Available,
+                            "this username is invalid!"
+                        );
+                    }
                 }
             }
 
-            let addr: &AccountId = &provider.money_address;
-            // send money to money_address (1000 - plan.max_refund_permille_policy) / 1000;
-            assert_eq!(
-                self.transfer(
-                    *addr,
-                    consts.price * (1000 - consts.max_refund_permille_policy) / 1000
-                ),
-                Ok(())
+            assert!(
+                self.env().transferred_balance() >= self.provider_register_fee,
+                "You have to pay a minimum amount to register in the contract!"
+            );
+            assert!(
+                !self.providers.contains_key(&caller),
+                "You can not register again in the contract!"
             );
 
             if address_has_not_username {
@@ -566,848 +1636,5523 @@ pub mod subscrypt {
                 self.username_to_address.insert(username, caller);
             }
 
-            if !self.users.contains_key(&caller) {
-                self.users.insert(
-                    caller,
-                    User {
-                        list_of_providers: Vec::new(),
-                        subscrypt_pass_hash: pass,
-                    },
-                );
-            }
-
-            let characteristics_values: Vec<String> = characteristics_values_encrypted.clone();
-
-            let subscription_record = SubscriptionRecord {
-                provider: provider_address,
-                plan: consts,
-                plan_index,
-                subscription_time: time,
-                characteristics_values_encrypted,
-                refunded: false,
+            let provider = Provider {
+                plan_count: 0,
+                money_address: address,
+                payment_manager: LinkedList::new(),
+                subscrypt_pass_hash,
             };
 
-            if let Some(plan_record) = self.records.get_mut(&(caller, provider_address)) {
-                self.plan_index_to_record_index.insert(
-                    (caller, provider_address, plan_index),
-                    plan_record.subscription_records.len().try_into().unwrap(),
-                );
-
-                plan_record.subscription_records.push(subscription_record);
-            } else {
-                self.users
-                    .get_mut(&caller)
-                    .unwrap()
-                    .list_of_providers
-                    .push(provider_address);
-
-                let plan_record: PlanRecord = PlanRecord {
-                    subscription_records: vec![subscription_record],
-                    pass_hash: pass,
-                };
-
-                self.records.insert((caller, provider_address), plan_record);
-
-                self.plan_index_to_record_index
-                    .insert((caller, provider_address, plan_index), 0);
-            }
-            self.add_entry(
-                provider_address,
-                (time + consts.duration - self.start_time) / 86400,
-                (self.env().transferred_balance() * consts.max_refund_permille_policy) / 1000,
+            self.providers.insert(caller, provider);
+            self.provider_list.push(caller);
+            self.add_plan(
+                durations,
+                prices,
+                max_refund_permille_policies,
+                plans_characteristics,
             );
-
-            
-            self.env().emit_event(SubscribeEvent {
-                provider: provider_address,
-                plan_index,
-                user_address: caller,
-                subscription_time: time,
-                duration: consts.duration,
-                price: consts.price,
-                characteristics: characteristics_values
-            });
+            self.env().emit_event(ProviderRegisterEvent { address });
         }
 
-        /// Renewing subscription of `plan_index` of the `provider_address` array of characteristics_values_encrypted
-        ///
-        /// In this function, we will first unlock (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000
-        /// in the `Linked List` of the contract and will also send that alongside the ((1000 - `plan.max_refund_permille_policy`) * `transferred_balance`) / 1000
-        /// of new recieved money and send that to provider. And (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000 will be locked in the `Linked List`
-        ///
-        /// # Note
-        ///
-        /// The start of that renewed subscription will be at the end of current subscription.
-        /// The current subscription can not be refunded.
-        ///
+        /// Add plans to `provider` storage
         ///
         /// # Panics
-        /// If paid amount is not equal to `price` of the plan
-        /// If plan is `disabled`
-        /// If `caller` is not already subscribed to plan
-        /// If `provider` does not exist
-        /// If `plan_index` is bigger than the length of `plans` of `provider_address`
+        ///
+        /// If the size of vectors passed to the method are different
+        /// If the caller is not a valid provider.
+        /// If a duration is `LIFETIME_DURATION`, lifetime plans are added by `add_lifetime_plan`
         ///
         /// # Emits
-        /// SubscribeEvent
+        ///  AddPlanEvent
         ///
         /// # Examples
-        /// Examples in `renew_works` in `tests/test.rs`
-        #[ink(message, payable)]
-        pub fn renew(
+        /// Examples in `add_plan_works` , `add_plan_fails_wrong_arguments`
+        #[ink(message)]
+        pub fn add_plan(
             &mut self,
-            provider_address: AccountId,
-            plan_index: u128,
-            new_characteristics_values: Vec<String>,
+            durations: Vec<u64>,
+            prices: Vec<u128>,
+            max_refund_permille_policies: Vec<u128>,
+            plan_characteristics: Vec<Vec<String>>,
         ) {
-            let caller: AccountId = self.env().caller();
-
-            if !self.plan_index_to_record_index.contains_key(&(
-                caller,
-                provider_address,
-                plan_index,
-            )) {
-                panic!("You should have been subscribed to this plan for renew!");
-            }
-
-            let last_index: u128 =
-                match self
-                    .plan_index_to_record_index
-                    .get(&(caller, provider_address, plan_index))
-                {
-                    Some(index) => *index,
-                    None => panic!("index is not valid!"),
-                };
-            let number: usize = last_index.try_into().unwrap();
-            let record = &self
-                .records
-                .get(&(caller, provider_address))
-                .unwrap()
-                .subscription_records[number];
-
-            if record.plan_index != plan_index
-                || record.refunded
-                || record.plan.duration + record.subscription_time < self.env().block_timestamp()
-            {
-                panic!("You should have been subscribed to this plan for renew!");
-            }
-            let provider = match self.providers.get(&provider_address) {
-                Some(provider) => provider,
-                None => panic!("Provider not existed in the contract!"),
-            };
-            let start_time: u64 = record.plan.duration + record.subscription_time;
-
-            let index: usize = plan_index.try_into().unwrap();
-            let consts: PlanConsts = provider.plans[index];
-
+            assert_eq!(prices.len(), durations.len(), "Wrong Number of Args");
             assert_eq!(
-                consts.price,
-                self.env().transferred_balance(),
-                "You have to pay exact plan price"
+                max_refund_permille_policies.len(),
+                durations.len(),
+                "Wrong Number of Args"
             );
-            assert!(!consts.disabled, "Plan is currently disabled by provider");
-
-            let plan_characteristics: Vec<String> = provider.plans_characteristics[index].clone();
             assert_eq!(
-                new_characteristics_values.len(),
+                durations.len(),
                 plan_characteristics.len(),
-                "invalid characteristic values!"
+                "Wrong Number of Args"
             );
 
-            let addr: &AccountId = &provider.money_address;
-            // send money to money_address (1000 - plan.max_refund_permille_policy) / 1000;
-            assert_eq!(
-                self.transfer(
-                    *addr,
-                    consts.price * (1000 - consts.max_refund_permille_policy) / 1000
-                ),
-                Ok(())
-            );
-            let promised_amount = record.plan.price * record.plan.max_refund_permille_policy / 1000;
-            assert_eq!(
-                self.transfer(provider.money_address, promised_amount),
-                Ok(())
-            );
-            let passed_time = record.plan.duration + record.subscription_time - self.start_time;
+            let caller = self.env().caller();
 
-            let subscription_record = SubscriptionRecord {
-                provider: provider_address,
-                plan: consts,
-                plan_index,
-                subscription_time: start_time,
-                characteristics_values_encrypted: new_characteristics_values.clone(),
-                refunded: false,
+            let provider = match self.providers.get_mut(&caller) {
+                Some(x) => x,
+                None => panic!("You should first register in the contract!"),
             };
 
-            self.remove_entry(
-                provider_address,
-                passed_time / 86400,
-                promised_amount / 1000,
-            );
-
-            let plan_record = self.records.get_mut(&(caller, provider_address)).unwrap();
+            let mut index: u128 = provider.plan_count;
 
-            self.plan_index_to_record_index.insert(
-                (caller, provider_address, plan_index),
-                plan_record.subscription_records.len().try_into().unwrap(),
-            );
+            for i in 0..durations.len() {
+                let plan: PlanConsts = PlanConsts {
+                    duration: durations[i],
+                    price: prices[i],
+                    max_refund_permille_policy: max_refund_permille_policies[i],
+                    disabled: false,
+                    kind: PlanKind::Recurring,
+                };
+                Self::check_plan_kind(&plan, PlanKind::Recurring);
+                self.plans.insert((caller, provider.plan_count), plan);
 
-            plan_record.subscription_records.push(subscription_record);
+                self.plans_characteristics.insert(
+                    (caller, provider.plan_count),
+                    plan_characteristics[i].clone(),
+                );
+                provider.plan_count += 1;
+            }
+            for i in 0..durations.len() {
+                self.env().emit_event(AddPlanEvent {
+                    provider: caller,
+                    duration: durations[i],
+                    price: prices[i],
+                    index
+                });
 
-            self.add_entry(
-                provider_address,
-                (start_time + consts.duration - self.start_time) / 86400,
-                (self.env().transferred_balance() * consts.max_refund_permille_policy) / 1000,
-            );
-            self.env().emit_event(SubscribeEvent {
-                provider: provider_address,
-                plan_index,
-                user_address: caller,
-                subscription_time: start_time,
-                duration: consts.duration,
-                price: consts.price,
-                characteristics: new_characteristics_values.clone(),
-            });
+                index += 1;
+            }
         }
 
-        /// Setting the `subscrypt_pass_hash` of caller to `pass`
+        /// Adding a lifetime plan to `provider` storage
         ///
         /// # Note
         ///
-        /// The `subscrypt_pass_hash` will also be set in `subscribe` function in first subscription
-        ///
+        /// Subscriptions of lifetime plans never expire and can not be renewed. They can be
+        /// refunded in the first `refund_window` seconds like a plan with `refund_window` duration,
+        /// and their refundable part is locked until the end of the refund window.
         ///
         /// # Panics
-        /// If `caller` does not exist in `users`
+        /// If the caller is not a valid provider
+        /// If `refund_window` is 0
+        ///
+        /// # Emits
+        /// AddPlanEvent
+        ///
+        /// # Examples
+        /// Examples in `lifetime_plan_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn set_user_subscrypt_pass(&mut self, pass: [u8; 32]) {
-            match self.users.get_mut(&self.env().caller()) {
-                Some(x) => x.subscrypt_pass_hash = pass,
-                None => panic!("User doesn't exist!"),
+        pub fn add_lifetime_plan(
+            &mut self,
+            price: u128,
+            max_refund_permille_policy: u128,
+            refund_window: u64,
+            plan_characteristics: Vec<String>,
+        ) {
+            assert!(refund_window > 0, "Refund window can not be 0!");
+            let caller = self.env().caller();
+            let provider = match self.providers.get_mut(&caller) {
+                Some(x) => x,
+                None => panic!("You should first register in the contract!"),
             };
+            let plan_index: u128 = provider.plan_count;
+            provider.plan_count += 1;
+
+            self.plans.insert(
+                (caller, plan_index),
+                PlanConsts {
+                    duration: LIFETIME_DURATION,
+                    price,
+                    max_refund_permille_policy,
+                    disabled: false,
+                    kind: PlanKind::Lifetime,
+                },
+            );
+            self.plans_characteristics
+                .insert((caller, plan_index), plan_characteristics);
+            self.lifetime_refund_windows
+                .insert((caller, plan_index), refund_window);
+            self.env().emit_event(AddPlanEvent {
+                provider: caller,
+                duration: LIFETIME_DURATION,
+                price,
+                index: plan_index,
+            });
         }
 
-        /// Setting the `pass_hash` of caller in specific provider to `pass`
+        /// Editing previously created plans of the `caller`
+        ///
+        /// # Note
+        ///
+        /// This will not effect the users that subscribed prior to the edition of plan
         ///
         /// # Panics
-        /// If `caller` does not exist in `users`
+        ///
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If `duration` is `LIFETIME_DURATION` and the plan is not `Lifetime` or vice versa
+        ///
+        /// # Examples
+        /// Examples of different situations in `edit_plan_works` and `edit_plan_works2` in `tests/test.rs`
         #[ink(message)]
-        pub fn subs_crypt_pass_hash_for_each_provider(
+        pub fn edit_plan(
             &mut self,
-            provider_address: AccountId,
-            pass: [u8; 32],
+            plan_index: u128,
+            duration: u64,
+            price: u128,
+            max_refund_permille_policies: u128,
+            disabled: bool,
         ) {
-            match self
-                .records
-                .get_mut(&(self.env().caller(), provider_address))
-            {
-                Some(x) => x.pass_hash = pass,
-                None => panic!("User doesn't exist!"),
+            let caller = self.env().caller();
+            self.apply_scheduled_edit(caller, plan_index);
+
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+
+            let plan: &mut PlanConsts = match self.plans.get_mut(&(caller, plan_index)) {
+                Some(x) => x,
+                None => panic!("please select a valid plan"),
             };
+
+            let kind: PlanKind = plan.kind;
+            plan.duration = duration;
+            plan.price = price;
+            plan.max_refund_permille_policy = max_refund_permille_policies;
+            plan.disabled = disabled;
+            let plan: PlanConsts = *plan;
+            Self::check_plan_kind(&plan, kind);
+            self.move_subscribers_to(caller, plan_index, plan);
         }
-        /// Setting the `subscrypt_pass_hash` of caller to `pass`
+
+        /// Scheduling an edit of a plan of the `caller` which will be applied at `effective_time`
         ///
         /// # Note
         ///
-        /// The `subscrypt_pass_hash` will also be set in `provider_register` function in first subscription
-        ///
+        /// If `grandfather` is true, users that subscribed prior to `effective_time` keep renewing
+        /// with the configs of their `SubscriptionRecord.renewal_plan`, otherwise they move to the
+        /// new configs at their next `renew`. Users that were moved by an earlier edit keep the
+        /// configs of that edit. A new schedule replaces the pending one.
         ///
         /// # Panics
-        /// If `caller` does not exist in `providers`
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If `effective_time` is not in future
+        /// If `plan.kind` is not the kind of the plan or `plan.duration` does not match it
+        ///
+        /// # Emits
+        /// `PlanEditScheduledEvent`
+        ///
+        /// # Examples
+        /// Examples in `schedule_plan_edit_works` and `schedule_plan_edit_works_after_edit` in
+        /// `tests/test.rs`
         #[ink(message)]
-        pub fn set_provider_subscrypt_pass(&mut self, pass: [u8; 32]) {
-            match self.providers.get_mut(&self.env().caller()) {
-                Some(x) => x.subscrypt_pass_hash = pass,
-                None => panic!("User doesn't exist!"),
-            };
+        pub fn schedule_plan_edit(
+            &mut self,
+            plan_index: u128,
+            plan: PlanConsts,
+            effective_time: u64,
+            grandfather: bool,
+        ) {
+            let caller = self.env().caller();
+            assert!(
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
+            );
+            assert!(
+                effective_time > self.env().block_timestamp(),
+                "Effective time should be in future!"
+            );
+            self.apply_scheduled_edit(caller, plan_index);
+            Self::check_plan_kind(&plan, self.plans.get(&(caller, plan_index)).unwrap().kind);
+            self.scheduled_edits.insert(
+                (caller, plan_index),
+                ScheduledEdit {
+                    plan,
+                    effective_time,
+                    grandfather,
+                },
+            );
+            self.env().emit_event(PlanEditScheduledEvent {
+                provider: caller,
+                plan_index,
+                duration: plan.duration,
+                price: plan.price,
+                max_refund_permille_policy: plan.max_refund_permille_policy,
+                disabled: plan.disabled,
+                effective_time,
+                grandfather,
+            });
         }
 
-        /// This function is used when providers want to collect the locked money for refund policy
-        ///
-        /// In this function, we will unlock that money which was locked in `subscribe` function via the
-        /// LinkedList mechanism, so providers can `withdraw` them when the due date passed.
-        ///
-        /// # Returns
-        /// `paid` amount is returned
+        /// Canceling the pending scheduled edit of a plan of the `caller`
         ///
         /// # Panics
-        /// If `provider` does not exist
+        /// If plan has no pending edit
         ///
         /// # Examples
-        /// Examples in `withdraw_works` and `withdraw_works2` in `tests/test.rs`
+        /// Examples in `schedule_plan_edit_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn withdraw(&mut self) -> u128 {
+        pub fn cancel_plan_edit(&mut self, plan_index: u128) {
+            let caller = self.env().caller();
+            self.apply_scheduled_edit(caller, plan_index);
             assert!(
-                self.providers.contains_key(&self.env().caller()),
-                "You are not a registered provider"
+                self.scheduled_edits.take(&(caller, plan_index)).is_some(),
+                "There is no scheduled edit for this plan!"
             );
-
-            let caller: AccountId = self.env().caller();
-            let t = self.process(caller, self.env().block_timestamp() / 86400);
-            if t.withdrawing_amount > 0 {
-                assert_eq!(self.transfer(caller, t.withdrawing_amount), Ok(()));
-            }
-
-            let linked_list: &mut LinkedList =
-                &mut self.providers.get_mut(&caller).unwrap().payment_manager;
-
-            linked_list.length -= t.reduced_length;
-            linked_list.head = t.current_linked_list_head;
-
-            return t.withdrawing_amount;
         }
 
-        /// `users` can use this function to easily refund their subscription as the policy of that
-        /// specific plan was set. The `users` will be paid back at most
-        /// (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000 and it will be linearly
-        /// decreased as time passed and will get to 0. The `provider` will get 0 at least and will linearly
-        /// get more if `user` refund later.
-        ///
-        /// # Returns
-        /// `paid` amount is returned
+        /// Setting the public profile of `caller`
         ///
         /// # Panics
-        /// If `provider` does not exist
+        /// If caller is not `provider`
         ///
         /// # Examples
-        /// Assume that `plan.max_refund_permille_policy` = 500 and `plan.price` = 100 the duration
-        /// of the plan is a month(30 days month). if `user` refund in first half of the month, then the user will
-        /// be paid 50. if `user` refund in day 20th of month then `user` will be paid 33.33 and `provider`
-        /// will be paid 16.66.
-        /// Other Examples in `refund_works` and `refund_works2` in `tests/test.rs`
+        /// Examples in `list_providers_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn refund(&mut self, provider_address: AccountId, plan_index: u128) -> u128 {
-            let caller: AccountId = self.env().caller();
-            let time: u64 = self.env().block_timestamp();
+        pub fn set_provider_profile(
+            &mut self,
+            display_name: String,
+            description: String,
+            website: String,
+            logo_hash: Option<[u8; 32]>,
+            category: String,
+        ) {
+            let caller = self.env().caller();
             assert!(
-                self.check_subscription(caller, provider_address, plan_index),
-                "You are not in this plan or already refunded"
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
             );
+            self.provider_profiles.insert(
+                caller,
+                ProviderProfile {
+                    display_name,
+                    description,
+                    website,
+                    logo_hash,
+                    category,
+                },
+            );
+        }
 
-            let last_index =
-                match self
-                    .plan_index_to_record_index
-                    .get(&(caller, provider_address, plan_index))
-                {
-                    Some(index) => index,
-                    None => panic!("index is not valid!"),
-                };
-
-            let number: usize = (*last_index).try_into().unwrap();
-            let record: &SubscriptionRecord = self
-                .records
-                .get(&(caller, provider_address))
-                .unwrap()
-                .subscription_records
-                .get(number)
-                .unwrap();
-
-            assert!(time - record.subscription_time < record.plan.duration);
-
-            let promised_amount = record.plan.price * record.plan.max_refund_permille_policy;
-            let price: u64 = (record.plan.price * 1000).try_into().unwrap();
-            let used: u64 = price * (time - record.subscription_time) / record.plan.duration;
-            let mut customer_portion_locked_money: u128 = (price - used).try_into().unwrap();
-
-            if customer_portion_locked_money > promised_amount {
-                // in this case the customer wants to refund very early so he want to get
-                // more than the amount of refund policy, so we can only give back just
-                // max_refund_permille_policy of his/her subscription. Whole locked money will go directly to
-                // account of the customer
-
-                customer_portion_locked_money = promised_amount;
-            } else {
-                // in this case the customer wants to refund, but he/she used most of his subscription time
-                // and now he/she will get portion of locked money, and the provider will get the rest of money
-
-                let provider_portion_locked_money =
-                    (promised_amount - customer_portion_locked_money) / 1000;
-                assert_eq!(
-                    self.transfer(
-                        self.providers.get(&provider_address).unwrap().money_address,
-                        provider_portion_locked_money
-                    ),
-                    Ok(())
-                );
-            }
-            assert_eq!(
-                self.transfer(caller, customer_portion_locked_money / 1000),
-                Ok(())
+        /// Setting name, description, details and visibility of a plan of `caller`
+        ///
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples in `get_provider_catalog_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_plan_metadata(
+            &mut self,
+            plan_index: u128,
+            name: String,
+            description: String,
+            uri: Option<String>,
+            content_hash: Option<[u8; 32]>,
+            visibility: Visibility,
+        ) {
+            let caller = self.env().caller();
+            assert!(
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
             );
-
-            let passed_time = record.plan.duration + record.subscription_time - self.start_time;
-            self.remove_entry(
-                provider_address,
-                passed_time / 86400,
-                promised_amount / 1000,
+            self.plan_metadata.insert(
+                (caller, plan_index),
+                PlanMetadata {
+                    name,
+                    description,
+                    uri,
+                    content_hash,
+                    visibility,
+                },
             );
-            self.records
-                .get_mut(&(caller, provider_address))
-                .unwrap()
-                .subscription_records
-                .get_mut(number)
-                .unwrap()
-                .refunded = true;
-            customer_portion_locked_money
         }
 
-        /// This function returns the withdrawable amount
+        /// Setting the capacity limit of a plan of `caller`
         ///
-        /// # Returns
-        /// `paid` amount is returned
+        /// # Note
+        ///
+        /// `max_active` = 0 removes the limit. If `waitlist_enabled` is true, users can join the
+        /// waitlist of the full plan by `join_waitlist`. When a place is freed by a refund or an
+        /// expiry, the first waitlisted user can subscribe in the next `claim_window` seconds. Only
+        /// plans with a limit count their active subscriptions, so subscriptions prior to the first
+        /// limit are not counted. A place of an expired subscription is freed at the end of its
+        /// last day.
+        /// Each call processes the expiries of at most `MAX_EXPIRY_DAYS` days, so if the plan is
+        /// not used for a longer time, its later expiries are freed by the next calls or by
+        /// `process_waitlist`.
         ///
         /// # Panics
-        /// If `provider` does not exist
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
         ///
         /// # Examples
-        /// Examples in `withdraw_works` and `withdraw_works2` in `tests/test.rs`
+        /// Examples in `waitlist_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn get_withdrawable_amount(&self) -> u128 {
+        pub fn set_plan_capacity(
+            &mut self,
+            plan_index: u128,
+            max_active: u128,
+            waitlist_enabled: bool,
+            claim_window: u64,
+        ) {
+            let caller = self.env().caller();
             assert!(
-                self.providers.contains_key(&self.env().caller()),
-                "You are not a registered provider"
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
             );
-
-            let caller: AccountId = self.env().caller();
-            let t = self.process(caller, self.env().block_timestamp() / 86400);
-            t.withdrawing_amount
+            if max_active == 0 {
+                self.plan_capacities.take(&(caller, plan_index));
+                self.waitlists.take(&(caller, plan_index));
+                self.waitlist_offers.take(&(caller, plan_index));
+                return;
+            }
+            self.plan_capacities.insert(
+                (caller, plan_index),
+                PlanCapacity {
+                    max_active,
+                    waitlist_enabled,
+                    claim_window,
+                },
+            );
+            // the count of a plan is kept when its limit is removed, so it is still valid if the
+            // limit is set again
+            if !self.active_counts.contains_key(&(caller, plan_index)) {
+                self.active_counts.insert(
+                    (caller, plan_index),
+                    ActiveCount {
+                        count: 0,
+                        processed_day: self.env().block_timestamp() / 86400,
+                    },
+                );
+            }
+            self.update_waitlist(caller, plan_index);
         }
 
-        /// This function indicate if `user` can authenticate with given `pass_phrase`
-        /// # Note
-        /// `user` are encouraged to have different `pass_phrase` for each provider
+        /// Removing expired subscriptions of a plan and offering the freed places to waitlisted
+        /// users, anyone can call it
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Emits
+        /// `WaitlistOfferEvent` for each offered place
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
+        /// # Examples
+        /// Examples in `waitlist_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn check_auth(
-            &self,
-            user: AccountId,
-            provider: AccountId,
-            pass_phrase: String,
-        ) -> bool {
-            return match self.records.get(&(user, provider)) {
-                Some(record) => {
-                    let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
-                    return encoded == record.pass_hash;
-                }
-                None => false,
-            };
+        pub fn process_waitlist(&mut self, provider_address: AccountId, plan_index: u128) {
+            self.update_waitlist(provider_address, plan_index);
         }
 
-        /// This function indicate if `username` can authenticate with given `pass_phrase`
+        /// Joining the FIFO waitlist of a full plan of `provider_address`
+        ///
         /// # Note
-        /// `user` are encouraged to have different `pass_phrase` for each provider
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// `subscribe` rejects a full plan, so waitlisted users subscribe with it after they are
+        /// offered a freed place and before the deadline of the offer.
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
-        #[ink(message)]
-        pub fn check_auth_with_username(
-            &self,
-            username: String,
-            provider: AccountId,
-            pass_phrase: String,
-        ) -> bool {
-            let user = match self.username_to_address.get(&username) {
-                Some(name) => *name,
-                None => panic!("this username is invalid!"),
-            };
-            self.check_auth(user, provider, pass_phrase)
-        }
-
-        /// This function indicate if `provider` can authenticate with given `pass_phrase`
+        /// # Panics
+        /// If plan has no waitlist
+        /// If `caller` is already subscribed to plan or is already waitlisted
+        /// If plan is not full
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Emits
+        /// WaitlistJoinEvent
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
+        /// # Examples
+        /// Examples in `waitlist_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn provider_check_auth(&self, provider: AccountId, pass_phrase: String) -> bool {
-            return match self.providers.get(&provider) {
-                Some(provider) => {
-                    let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
-                    return encoded == provider.subscrypt_pass_hash;
-                }
-                None => false,
-            };
+        pub fn join_waitlist(&mut self, provider_address: AccountId, plan_index: u128) {
+            let caller: AccountId = self.env().caller();
+            let capacity: PlanCapacity =
+                match self.plan_capacities.get(&(provider_address, plan_index)) {
+                    Some(capacity) if capacity.waitlist_enabled => *capacity,
+                    _ => panic!("Plan has no waitlist!"),
+                };
+            assert!(
+                !self.check_subscription(caller, provider_address, plan_index),
+                "You are already subscribed to this plan!"
+            );
+            self.update_waitlist(provider_address, plan_index);
+            assert!(
+                !self.has_free_place(provider_address, plan_index, caller, &capacity),
+                "Plan is not full!"
+            );
+            let mut waitlist: Vec<AccountId> = self
+                .waitlists
+                .take(&(provider_address, plan_index))
+                .unwrap_or_default();
+            assert!(!waitlist.contains(&caller), "You are already waitlisted!");
+            waitlist.push(caller);
+            self.waitlists
+                .insert((provider_address, plan_index), waitlist);
+            self.env().emit_event(WaitlistJoinEvent {
+                provider: provider_address,
+                user_address: caller,
+                plan_index,
+            });
         }
 
-        /// This function indicate if `provider` can authenticate with given `pass_phrase` and `username`
+        /// Adding `accounts` to the allowlist of a plan of `caller`
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Note
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
+        /// Allowlist is only checked for plans with `Visibility::InviteOnly`
+        ///
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples in `allowlist_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn provider_check_auth_with_username(
-            &self,
-            username: String,
-            pass_phrase: String,
-        ) -> bool {
-            let address = match self.username_to_address.get(&username) {
-                Some(name) => *name,
-                None => panic!("this username is invalid!"),
-            };
-            self.provider_check_auth(address, pass_phrase)
+        pub fn add_to_allowlist(&mut self, plan_index: u128, accounts: Vec<AccountId>) {
+            let caller = self.env().caller();
+            assert!(
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
+            );
+            for account in accounts.iter() {
+                self.plan_allowlists
+                    .insert((caller, plan_index, *account), AllowlistEntry::Added);
+            }
         }
 
-        /// This function indicate if `user` can authenticate with given `pass_phrase`
+        /// Removing `accounts` from the allowlist of a plan of `caller`
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Note
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
+        /// This will not effect the active subscriptions of removed accounts. Removed accounts can
+        /// not add themselves again by `submit_allowlist_proof` until the merkle root is changed.
+        ///
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples in `allowlist_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn user_check_auth(&self, user: AccountId, pass_phrase: String) -> bool {
-            return match self.users.get(&user) {
-                Some(user) => {
-                    let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
-                    return encoded == user.subscrypt_pass_hash;
+        pub fn remove_from_allowlist(&mut self, plan_index: u128, accounts: Vec<AccountId>) {
+            let caller = self.env().caller();
+            assert!(
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
+            );
+            let root: Option<[u8; 32]> = self.get_allowlist_merkle_root(caller, plan_index);
+            for account in accounts.iter() {
+                match root {
+                    Some(root) => {
+                        self.plan_allowlists.insert(
+                            (caller, plan_index, *account),
+                            AllowlistEntry::Removed(root),
+                        );
+                    }
+                    None => {
+                        self.plan_allowlists.take(&(caller, plan_index, *account));
+                    }
                 }
-                None => false,
-            };
+            }
         }
 
-        /// This function indicate if `user` can authenticate with given `pass_phrase` and `username`
+        /// Setting the merkle root of allowed accounts of a plan of `caller`, `None` removes it
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Note
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
-        #[ink(message)]
-        pub fn user_check_auth_with_username(&self, username: String, pass_phrase: String) -> bool {
-            let address = match self.username_to_address.get(&username) {
-                Some(name) => *name,
-                None => panic!("this username is invalid!"),
-            };
-            self.user_check_auth(address, pass_phrase)
-        }
-
-        /// This function indicate if `username` is available
+        /// Leaves are sha2 hash of encoded `AccountId` and each parent is sha2 hash of its two
+        /// children in ascending order. Users prove their membership by `submit_allowlist_proof`.
+        /// Proofs of the previous root are not valid anymore, so the users of both roots have to
+        /// submit their proofs again.
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
-        #[ink(message)]
-        pub fn is_username_available(&self, username: String) -> bool {
-            !self.username_to_address.contains_key(&username)
-        }
-
-
-        /// This function returns provider money address
+        /// # Examples
+        /// Examples in `submit_allowlist_proof_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn get_money_address(&self) -> ink_env::AccountId {
+        pub fn set_allowlist_merkle_root(&mut self, plan_index: u128, root: Option<[u8; 32]>) {
             let caller = self.env().caller();
-
-            let provider = match self.providers.get(&caller) {
-                Some(x) => x,
-                None => panic!("You should first register in the contract!"),
+            assert!(
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
+            );
+            match root {
+                Some(root) => self.plan_merkle_roots.insert((caller, plan_index), root),
+                None => self.plan_merkle_roots.take(&(caller, plan_index)),
             };
-
-            provider.money_address
         }
 
-        /// This function indicate `username` of `caller`
+        /// `caller` can add herself to the allowlist of a plan by proving her membership in the
+        /// merkle root of that plan
         ///
-        /// # Returns
-        /// `bool` is returned which shows the correctness of auth
+        /// # Panics
+        /// If plan has no merkle root
+        /// If `caller` is removed from the allowlist while the plan has the current merkle root
+        /// If `proof` is not valid for `caller`
         ///
-        /// # Example
-        /// Examples in `check_auth_works` in `tests/test.rs`
+        /// # Examples
+        /// Examples in `submit_allowlist_proof_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn get_username(&self) -> String {
+        pub fn submit_allowlist_proof(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            proof: Vec<[u8; 32]>,
+        ) {
             let caller: AccountId = self.env().caller();
-            match self.address_to_username.get(&caller) {
-                Some(username) => username.clone(),
-                None => panic!("this address has not a valid associated username!"),
+            let root: [u8; 32] = match self.plan_merkle_roots.get(&(provider_address, plan_index)) {
+                Some(root) => *root,
+                None => panic!("This plan has no merkle root!"),
+            };
+            let mut node = <Sha2x256 as HashOutput>::Type::default();
+            ink_env::hash_encoded::<Sha2x256, _>(&caller, &mut node);
+            for sibling in proof.iter() {
+                let mut pair: Vec<u8> = Vec::new();
+                if node <= *sibling {
+                    pair.extend_from_slice(&node);
+                    pair.extend_from_slice(sibling);
+                } else {
+                    pair.extend_from_slice(sibling);
+                    pair.extend_from_slice(&node);
+                }
+                ink_env::hash_bytes::<Sha2x256>(&pair, &mut node);
             }
+            assert!(
+                self.plan_allowlists
+                    .get(&(provider_address, plan_index, caller))
+                    != Some(&AllowlistEntry::Removed(root)),
+                "You are removed from the allowlist of this plan!"
+            );
+            assert_eq!(node, root, "Invalid allowlist proof!");
+            self.plan_allowlists.insert(
+                (provider_address, plan_index, caller),
+                AllowlistEntry::Proven(root),
+            );
         }
 
+        /// Adding new characteristics to existing plans
+        ///
+        /// # Note
+        ///
+        /// This will not effect the users that subscribed prior to the edition of plan
+        ///
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples of different situations in `add_characteristic_for_plan_works`
         #[ink(message)]
-        pub fn get_address_by_username(&self, username: String) -> AccountId {
-            match self.username_to_address.get(&username) {
-                Some(address) => *address,
-                None => panic!("this username has not a valid associated address!"),
+        pub fn add_characteristic_for_plan(
+            &mut self,
+            plan_index: u128,
+            characteristics: Vec<String>,
+        ) {
+            let caller = self.env().caller();
+
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+
+            let plan: &mut Vec<String> =
+                match self.plans_characteristics.get_mut(&(caller, plan_index)) {
+                    Some(x) => x,
+                    None => panic!("please select a valid plan"),
+                };
+
+            for i in 0..characteristics.len() {
+                plan.push(characteristics[i].clone());
+            }
+
+            let schema: &mut CharacteristicSchema = self.edit_schema(caller, plan_index);
+            if !schema.specs.is_empty() {
+                for _ in 0..characteristics.len() {
+                    schema.specs.push(CharacteristicSpec {
+                        kind: CharacteristicType::Text,
+                        required: false,
+                        max_size: 0,
+                    });
+                }
             }
         }
 
-        /// `user` can use this function to retrieve her whole subscription history to
-        /// different providers.
+        /// Setting the typed schema of characteristics of a plan of `caller`
+        ///
         /// # Note
-        /// `user` has to provide their `pass_phrase` which will be used in SubsCrypt dashboard
         ///
-        /// # Returns
-        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        /// Each spec belongs to the key with the same index in plan characteristics. Passing an
+        /// empty vector makes the plan untyped again.
         ///
-        /// # Example
-        /// Examples in `retrieve_whole_data_with_password_works` in `tests/test.rs`
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If length of `specs` is not equal to the number of plan characteristics
+        /// If an `Enum` spec has no options
+        ///
+        /// # Examples
+        /// Examples in `set_characteristic_schema_works` and
+        /// `set_characteristic_schema_fails_empty_enum` in `tests/test.rs`
         #[ink(message)]
-        pub fn retrieve_whole_data_with_username(
-            &self,
-            username: String,
-            pass_phrase: String,
-        ) -> Vec<SubscriptionRecord> {
-            let user = match self.username_to_address.get(&username) {
-                Some(name) => *name,
-                None => panic!("this username is invalid!"),
-            };
-            let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
-            assert_eq!(
-                encoded,
-                self.users.get(&user).unwrap().subscrypt_pass_hash,
-                "Wrong auth"
+        pub fn set_characteristic_schema(
+            &mut self,
+            plan_index: u128,
+            specs: Vec<CharacteristicSpec>,
+        ) {
+            let caller = self.env().caller();
+            let characteristics_count: usize =
+                self.get_plan_characteristics(caller, plan_index).len();
+            assert!(
+                specs.is_empty() || specs.len() == characteristics_count,
+                "Wrong Number of Args"
             );
-            self.retrieve_whole_data(user)
+            for spec in specs.iter() {
+                if let CharacteristicType::Enum(options) = &spec.kind {
+                    assert!(!options.is_empty(), "Enum characteristic needs options!");
+                }
+            }
+            self.edit_schema(caller, plan_index).specs = specs;
         }
 
-        /// `user` can use this function to retrieve her whole subscription history to
-        /// different providers with their wallet.
+        /// Removing the characteristic with `characteristic_index` from a plan of `caller`
         ///
-        /// # Returns
-        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        /// # Note
         ///
-        /// # Example
-        /// Examples in `retrieve_whole_data_with_wallet_works` in `tests/test.rs`
+        /// This will not effect the users that subscribed prior to the edition of plan
+        ///
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` or `characteristic_index` is not valid
+        ///
+        /// # Examples
+        /// Examples in `set_characteristic_schema_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn retrieve_whole_data_with_wallet(&self) -> Vec<SubscriptionRecord> {
-            let caller: AccountId = self.env().caller();
-            self.retrieve_whole_data(caller)
+        pub fn remove_characteristic_for_plan(
+            &mut self,
+            plan_index: u128,
+            characteristic_index: u32,
+        ) {
+            let characteristic_number: usize = characteristic_index.try_into().unwrap();
+            let caller = self.env().caller();
+
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            let plan: &mut Vec<String> =
+                match self.plans_characteristics.get_mut(&(caller, plan_index)) {
+                    Some(x) => x,
+                    None => panic!("please select a valid plan"),
+                };
+            assert!(
+                characteristic_number < plan.len(),
+                "please select a valid characteristic"
+            );
+            plan.remove(characteristic_number);
+
+            let schema: &mut CharacteristicSchema = self.edit_schema(caller, plan_index);
+            if !schema.specs.is_empty() {
+                schema.specs.remove(characteristic_number);
+            }
         }
 
-        /// `user` can use this function to retrieve her subscriptions history for a specific
-        /// provider.
+        /// Reordering characteristics of a plan of `caller`
         ///
         /// # Note
-        /// `user` has to provide their `pass_phrase` for that provider.
         ///
-        /// # Returns
-        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        /// `order[i]` is the current index of the characteristic that will be placed at index `i`
         ///
-        /// # Example
-        /// Examples in `retrieve_data_with_password_works` in `tests/test.rs`
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is not valid
+        /// If `order` is not a permutation of current characteristic indexes
+        ///
+        /// # Examples
+        /// Examples in `set_characteristic_schema_works` in `tests/test.rs`
         #[ink(message)]
-        pub fn retrieve_data_with_username(
-            &self,
+        pub fn reorder_characteristics_for_plan(&mut self, plan_index: u128, order: Vec<u32>) {
+            let caller = self.env().caller();
+
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            let plan: &mut Vec<String> =
+                match self.plans_characteristics.get_mut(&(caller, plan_index)) {
+                    Some(x) => x,
+                    None => panic!("please select a valid plan"),
+                };
+            let mut seen: Vec<bool> = vec![false; plan.len()];
+            for i in order.iter() {
+                let j: usize = (*i).try_into().unwrap();
+                assert!(j < plan.len() && !seen[j], "Wrong order of characteristics");
+                seen[j] = true;
+            }
+            assert_eq!(order.len(), plan.len(), "Wrong order of characteristics");
+            *plan = order.iter().map(|i| plan[*i as usize].clone()).collect();
+
+            let schema: &mut CharacteristicSchema = self.edit_schema(caller, plan_index);
+            if !schema.specs.is_empty() {
+                schema.specs = order
+                    .iter()
+                    .map(|i| schema.specs[*i as usize].clone())
+                    .collect();
+            }
+        }
+
+        /// Disabling previously created plans of the `caller`
+        ///
+        /// # Note
+        ///
+        /// This will not effect the users that subscribed prior to the edition of plan
+        ///
+        /// # Panics
+        ///
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples in `change_disable_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn change_disable(&mut self, plan_index: u128) {
+            let caller = self.env().caller();
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            match self.plans.get_mut(&(caller, plan_index)) {
+                Some(plan) => plan.disabled = !plan.disabled,
+                None => panic!("please select a valid plan"),
+            }
+        }
+
+        /// Setting the number of seats of `plan_index` of the `caller`
+        ///
+        /// A plan with more than one seat is a team plan. The subscriber of a team plan becomes
+        /// the seat admin of her subscription and can assign the remaining seats to other members
+        /// with `add_seats`. `PlanConsts.price` is charged per seat.
+        ///
+        /// # Note
+        ///
+        /// Seats that are already assigned are not revoked if `max_seats` is decreased.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If `max_seats` is 0
+        ///
+        /// # Examples
+        /// Examples in `add_seats_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_plan_max_seats(&mut self, plan_index: u128, max_seats: u128) {
+            let caller = self.env().caller();
+            let provider = match self.providers.get(&caller) {
+                Some(x) => x,
+                None => panic!("You should first register in the contract!"),
+            };
+            assert!(
+                provider.plan_count > plan_index,
+                "please select a valid plan"
+            );
+            assert!(max_seats > 0, "A plan should have at least one seat");
+            self.plan_max_seats.insert((caller, plan_index), max_seats);
+        }
+
+        /// Subscribing to `plan_index` of the `provider_address` with `Sha2x256` hashed `pass` and array of characteristics_values_encrypted
+        ///
+        /// In this function, we will lock (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000
+        /// in the `Linked List` of the contract and will transfer the rest of paid money directly to provider
+        /// after deducting the platform commission (`platform_fee_basis_points`)
+        ///
+        /// If the subscription is referred by `referrer`, (`commission_permille` of `referrer` * provider portion) / 1000
+        /// of the money that is transferred directly to provider will be kept for `referrer`
+        /// which can be collected by `withdraw_affiliate_earnings`.
+        ///
+        /// # Note
+        ///
+        /// The `subscrypt_pass_hash` will only be set if it's the first subscription of the `caller` to the `SubsCrypt` platform
+        /// `caller` can not subscribe to same `plan_index` of the same `provider_address` but
+        /// it can subscribe to different `plan_index` of same `provider_address` .
+        /// This line of code checks that if you previously subscribed to `provider_address` and if it's the first time
+        /// then `list_of_providers` will be updated.
+        ///  `if !self.records.contains_key(&(caller, provider_address)) `
+        ///
+        /// If the subscription is refunded, the commission is clawed back proportional to the
+        /// refunded amount. The part that is already withdrawn by `referrer` is recovered from her
+        /// next commissions of `provider_address`, so the provider does not get it back if
+        /// `referrer` is not referring anymore.
+        ///
+        ///
+        /// # Panics
+        /// If paid amount is not equal to `price` of the plan
+        /// If plan is `disabled`
+        /// If `caller` is already subscribed to plan
+        /// If `provider` does not exist
+        /// If `plan_index` is bigger than the length of `plans` of `provider_address`
+        /// If 'username' has already been taken
+        /// If plan is full and its freed place is not offered to `caller` by `join_waitlist`
+        /// If `referrer` is not an affiliate of `provider_address`
+        /// If `referrer` is `caller`
+        ///
+        /// # Emits
+        /// SubscribeEvent
+        /// ReferralEvent if the subscription is referred
+        ///
+        /// # Examples
+        /// Examples in `subscribe_works`, `subscribe_fails_insufficient_paying` and
+        /// `subscribe_works_with_referrer` in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn subscribe(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            pass: [u8; 32],
             username: String,
+            characteristics_values_encrypted: Vec<String>,
+            referrer: Option<AccountId>,
+        ) {
+            self.subscribe_plan(
+                provider_address,
+                plan_index,
+                pass,
+                username,
+                characteristics_values_encrypted,
+                SubscribeOptions {
+                    referrer,
+                    periods: 1,
+                },
+            );
+        }
+
+        /// Renewing subscription of `plan_index` of the `provider_address` array of characteristics_values_encrypted
+        ///
+        /// In this function, we will first unlock (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000
+        /// in the `Linked List` of the contract and will also send that alongside the ((1000 - `plan.max_refund_permille_policy`) * `transferred_balance`) / 1000
+        /// of new recieved money and send that to provider. And (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000 will be locked in the `Linked List`
+        ///
+        /// # Note
+        ///
+        /// The start of that renewed subscription will be at the end of current subscription.
+        /// The current subscription can not be refunded.
+        /// Add-ons of the current subscription which are not disabled are renewed alongside it for
+        /// their whole `price`.
+        /// Team subscriptions renew every assigned seat for `price`, and the locked amount of all
+        /// seats of the current subscription, including the ones added by `add_seats`, is unlocked.
+        ///
+        ///
+        /// # Panics
+        /// If paid amount is not equal to `price` of the plan and its renewed add-ons
+        /// If plan is `disabled`
+        /// If `caller` is not already subscribed to plan
+        /// If `provider` does not exist
+        /// If `plan_index` is bigger than the length of `plans` of `provider_address`
+        ///
+        /// # Emits
+        /// SubscribeEvent
+        ///
+        /// # Examples
+        /// Examples in `renew_works` and `renew_works_team_subscription` in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn renew(
+            &mut self,
             provider_address: AccountId,
-            pass_phrase: String,
-        ) -> Vec<SubscriptionRecord> {
-            let user = match self.username_to_address.get(&username) {
-                Some(name) => *name,
-                None => panic!("this username is invalid!"),
-            };
-            let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
-            assert_eq!(
-                encoded,
-                self.records
-                    .get(&(user, provider_address))
-                    .unwrap()
-                    .pass_hash,
-                "Wrong auth"
+            plan_index: u128,
+            new_characteristics_values: Vec<String>,
+        ) {
+            let caller: AccountId = self.env().caller();
+            self.apply_scheduled_edit(provider_address, plan_index);
+
+            if !self.plan_index_to_record_index.contains_key(&(
+                caller,
+                provider_address,
+                plan_index,
+            )) {
+                panic!("You should have been subscribed to this plan for renew!");
+            }
+
+            let last_index: u128 =
+                match self
+                    .plan_index_to_record_index
+                    .get(&(caller, provider_address, plan_index))
+                {
+                    Some(index) => *index,
+                    None => panic!("index is not valid!"),
+                };
+            let record = self
+                .subscription_records
+                .get(&(caller, provider_address, last_index))
+                .unwrap();
+
+            if record.plan_index != plan_index
+                || record.refunded
+                || record
+                    .subscription_time
+                    .saturating_add(record.plan.duration)
+                    < self.env().block_timestamp()
+            {
+                panic!("You should have been subscribed to this plan for renew!");
+            }
+            let provider = match self.providers.get(&provider_address) {
+                Some(provider) => provider,
+                None => panic!("Provider not existed in the contract!"),
+            };
+            assert!(
+                record.plan.kind != PlanKind::Lifetime,
+                "Lifetime subscriptions can not be renewed!"
+            );
+            assert!(
+                record.bundle_index.is_none(),
+                "Bundle subscriptions can not be renewed!"
+            );
+            assert!(
+                !record.complimentary,
+                "Complimentary subscriptions can not be renewed!"
+            );
+            assert!(
+                !self.is_disputed(caller, provider_address, plan_index),
+                "This subscription is disputed!"
+            );
+            let start_time: u64 = record.plan.duration + record.subscription_time;
+            let previous: PlanConsts = self.renewal_plan(provider_address, plan_index, record);
+
+            let plan_consts: PlanConsts = *self.plans.get(&(provider_address, plan_index)).unwrap();
+            // pre-paid imported records have no price to keep, so they renew with the current configs
+            let consts: PlanConsts = if previous.price > 0 {
+                PlanConsts {
+                    disabled: plan_consts.disabled,
+                    ..previous
+                }
+            } else {
+                plan_consts
+            };
+
+            let add_ons: Vec<(u128, u128)> =
+                self.renewed_add_ons(caller, provider_address, plan_index, last_index);
+            let add_ons_price: u128 = add_ons.iter().map(|x| x.1).sum();
+            // team subscriptions renew every assigned seat alongside the admin's own seat
+            assert_eq!(
+                consts.price * self.seat_count(caller, provider_address, plan_index)
+                    + add_ons_price,
+                self.env().transferred_balance(),
+                "You have to pay exact plan price"
+            );
+            assert!(!consts.disabled, "Plan is currently disabled by provider");
+
+            let plan_characteristics: Vec<String> = self
+                .plans_characteristics
+                .get(&(provider_address, plan_index))
+                .unwrap()
+                .clone();
+            assert_eq!(
+                new_characteristics_values.len(),
+                plan_characteristics.len(),
+                "invalid characteristic values!"
+            );
+            self.validate_characteristics(
+                provider_address,
+                plan_index,
+                &new_characteristics_values,
+            );
+
+            let money_address: AccountId = provider.money_address;
+            let promised_amount = record.plan.price * record.plan.max_refund_permille_policy / 1000;
+            let passed_time = record.plan.duration + record.subscription_time - self.start_time;
+            let periods: u64 = record.periods;
+            let plan: PlanConsts = record.plan;
+            let subscription_time: u64 = record.subscription_time;
+            // send money to money_address (1000 - plan.max_refund_permille_policy) / 1000;
+            assert_eq!(
+                self.pay_provider(
+                    provider_address,
+                    money_address,
+                    self.env().transferred_balance() * (1000 - consts.max_refund_permille_policy)
+                        / 1000
+                ),
+                Ok(())
+            );
+            let released: u128 = if periods > 1 {
+                self.release_period_locks(provider_address, &plan, subscription_time, periods)
+            } else {
+                self.remove_entry(provider_address, passed_time / 86400, promised_amount);
+                promised_amount
+            };
+            // locks of the other seats end with the admin's own seat, so they are released too
+            let seats_locked: u128 = self
+                .seat_locks
+                .take(&(caller, provider_address, last_index))
+                .unwrap_or(0);
+            self.remove_entry(provider_address, passed_time / 86400, seats_locked);
+            assert_eq!(
+                self.pay_provider(provider_address, money_address, released + seats_locked),
+                Ok(())
+            );
+
+            let subscription_record = SubscriptionRecord {
+                provider: provider_address,
+                plan: consts,
+                plan_index,
+                subscription_time: start_time,
+                characteristics_values_encrypted: new_characteristics_values.clone(),
+                key_version: self.encryption_key_version(provider_address),
+                characteristics_version: 0,
+                schema_version: self.schema_version(provider_address, plan_index),
+                periods: 1,
+                renewal_plan: consts,
+                plan_epoch: self.plan_epoch(provider_address, plan_index),
+                bundle_index: None,
+                complimentary: false,
+                refunded: false,
+            };
+
+            self.referrals.take(&(caller, provider_address, plan_index));
+            let plan_record = self.records.get_mut(&(caller, provider_address)).unwrap();
+            let record_index: u128 = plan_record.record_count;
+            plan_record.record_count += 1;
+
+            self.plan_index_to_record_index
+                .insert((caller, provider_address, plan_index), record_index);
+
+            self.subscription_records.insert(
+                (caller, provider_address, record_index),
+                subscription_record,
+            );
+            let seat_count: u128 = self.seat_count(caller, provider_address, plan_index);
+            if seat_count > 1 {
+                self.seat_locks.insert(
+                    (caller, provider_address, record_index),
+                    consts.price * (seat_count - 1) * consts.max_refund_permille_policy / 1000,
+                );
+            }
+            let add_on_purchases: Vec<AddOnPurchase> = add_ons
+                .iter()
+                .map(|(add_on_index, price)| AddOnPurchase {
+                    add_on_index: *add_on_index,
+                    record_index,
+                    start_time,
+                    lock_end: start_time + consts.duration,
+                    price: *price,
+                })
+                .collect();
+            self.add_on_purchases
+                .insert((caller, provider_address, plan_index), add_on_purchases);
+
+            self.add_entry(
+                provider_address,
+                (start_time + consts.duration - self.start_time) / 86400,
+                (self.env().transferred_balance() * consts.max_refund_permille_policy) / 1000,
+            );
+            self.set_active_subscription(
+                provider_address,
+                plan_index,
+                caller,
+                start_time + consts.duration,
+            );
+            self.add_plan_stats(
+                provider_address,
+                plan_index,
+                false,
+                self.env().transferred_balance(),
+            );
+            self.env().emit_event(SubscribeEvent {
+                provider: provider_address,
+                plan_index,
+                user_address: caller,
+                subscription_time: start_time,
+                duration: consts.duration,
+                price: consts.price,
+                characteristics: new_characteristics_values.clone(),
+            });
+        }
+
+        /// Assigning seats of the active team subscription of `caller` to `members`
+        ///
+        /// Each seat costs `plan.price` prorated to the remaining time of the admin's subscription.
+        /// Like `subscribe`, (`plan.max_refund_permille_policy` * `seat price`) / 1000 of each seat will
+        /// be locked in the `Linked List` until the end of the subscription and the rest is sent to
+        /// the provider. Any paid amount above the total seat price is sent back to `caller`.
+        ///
+        /// # Note
+        ///
+        /// Refunding the admin's subscription only refunds the admin's own seat. Seats are kept on
+        /// `renew`, but they are revoked when the admin's subscription is refunded, revoked or
+        /// disputed, or when she subscribes again after it ends. A member whose seat admin's
+        /// subscription is expired can be assigned a seat by another admin.
+        ///
+        /// # Panics
+        /// If `caller` has no active subscription to `plan_index` of `provider_address`
+        /// If seats would exceed `plan_max_seats` of the plan
+        /// If a member is `caller` or already has a seat of an active subscription in this plan
+        /// If paid amount is less than the prorated price of the seats
+        ///
+        /// # Emits
+        /// SeatAddedEvent
+        ///
+        /// # Examples
+        /// Examples in `add_seats_works`, `add_seats_cleared_on_refund` and `add_seats_fails_no_free_seat`
+        /// in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn add_seats(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            members: Vec<AccountId>,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+
+            let (plan, end_time): (PlanConsts, u64) =
+                match self.get_active_record(caller, provider_address, plan_index) {
+                    Some(record) => (
+                        record.plan,
+                        record
+                            .subscription_time
+                            .saturating_add(record.plan.duration),
+                    ),
+                    None => {
+                        panic!("You should have been subscribed to this plan for adding seats!")
+                    }
+                };
+
+            let max_seats: u128 = *self
+                .plan_max_seats
+                .get(&(provider_address, plan_index))
+                .unwrap_or(&1);
+            let members_len: u128 = members.len().try_into().unwrap();
+            assert!(
+                self.seat_count(caller, provider_address, plan_index) + members_len <= max_seats,
+                "There is no free seat in this plan!"
+            );
+
+            let seat_price: u128 = Self::prorated_price(&plan, end_time, time);
+            let total_price: u128 = seat_price * members_len;
+            assert!(
+                self.env().transferred_balance() >= total_price,
+                "You have to pay the price of seats"
+            );
+
+            for member in members.iter() {
+                assert!(*member != caller, "You already have a seat in this plan!");
+                if let Some(admin) = self
+                    .seat_admins
+                    .get(&(*member, provider_address, plan_index))
+                    .copied()
+                {
+                    assert!(
+                        self.get_active_record(admin, provider_address, plan_index)
+                            .is_none(),
+                        "This member already has a seat in this plan!"
+                    );
+                    // the seat of an expired team subscription is freed
+                    self.seats
+                        .get_mut(&(admin, provider_address, plan_index))
+                        .unwrap()
+                        .retain(|x| x != member);
+                }
+                self.seat_admins
+                    .insert((*member, provider_address, plan_index), caller);
+            }
+
+            let money_address: AccountId =
+                self.providers.get(&provider_address).unwrap().money_address;
+            assert_eq!(
+                self.pay_provider(
+                    provider_address,
+                    money_address,
+                    total_price * (1000 - plan.max_refund_permille_policy) / 1000
+                ),
+                Ok(())
+            );
+            if self.env().transferred_balance() > total_price {
+                assert_eq!(
+                    self.transfer(caller, self.env().transferred_balance() - total_price),
+                    Ok(())
+                );
+            }
+            // seats of lifetime plans are locked only for the refund window
+            let lock_end: u64 = end_time.min(time.saturating_add(self.refund_duration(
+                provider_address,
+                plan_index,
+                &plan,
+            )));
+            self.add_entry(
+                provider_address,
+                (lock_end - self.start_time) / 86400,
+                (total_price * plan.max_refund_permille_policy) / 1000,
+            );
+            let record_index: u128 = *self
+                .plan_index_to_record_index
+                .get(&(caller, provider_address, plan_index))
+                .unwrap();
+            let seats_locked: u128 = self
+                .seat_locks
+                .get(&(caller, provider_address, record_index))
+                .copied()
+                .unwrap_or(0);
+            self.seat_locks.insert(
+                (caller, provider_address, record_index),
+                seats_locked + (total_price * plan.max_refund_permille_policy) / 1000,
+            );
+
+            match self.seats.get_mut(&(caller, provider_address, plan_index)) {
+                Some(list) => list.extend(members.iter().copied()),
+                None => {
+                    self.seats
+                        .insert((caller, provider_address, plan_index), members.clone());
+                }
+            }
+
+            for member in members {
+                self.env().emit_event(SeatAddedEvent {
+                    provider: provider_address,
+                    plan_index,
+                    admin: caller,
+                    member,
+                    price: seat_price,
+                });
+            }
+        }
+
+        /// Revoking the seat of `member` from the team subscription of `caller`
+        ///
+        /// # Note
+        ///
+        /// The paid amount of the revoked seat is not refunded, the seat can be assigned to
+        /// another member by paying its prorated price again.
+        ///
+        /// # Panics
+        /// If `member` has no seat assigned by `caller` in `plan_index` of `provider_address`
+        ///
+        /// # Emits
+        /// SeatRevokedEvent
+        ///
+        /// # Examples
+        /// Examples in `add_seats_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn revoke_seat(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            member: AccountId,
+        ) {
+            let caller: AccountId = self.env().caller();
+            match self
+                .seat_admins
+                .get(&(member, provider_address, plan_index))
+            {
+                Some(admin) => assert!(*admin == caller, "This member has no seat in your plan!"),
+                None => panic!("This member has no seat in your plan!"),
+            }
+            self.seat_admins
+                .take(&(member, provider_address, plan_index));
+            self.seats
+                .get_mut(&(caller, provider_address, plan_index))
+                .unwrap()
+                .retain(|x| *x != member);
+
+            self.env().emit_event(SeatRevokedEvent {
+                provider: provider_address,
+                plan_index,
+                admin: caller,
+                member,
+            });
+        }
+
+        /// Adding an optional paid add-on to `plan_index` of the `caller`
+        ///
+        /// # Note
+        ///
+        /// `price` is the price of the add-on for a whole duration of the plan. Add-ons can only be
+        /// purchased on top of an active subscription of the plan by `purchase_add_on`.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples in `add_on_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn add_add_on(&mut self, plan_index: u128, name: String, price: u128) {
+            let caller: AccountId = self.env().caller();
+            match self.providers.get(&caller) {
+                Some(provider) => assert!(
+                    provider.plan_count > plan_index,
+                    "please select a valid plan"
+                ),
+                None => panic!("You should first register in the contract!"),
+            }
+            let add_on: AddOn = AddOn {
+                name,
+                price,
+                disabled: false,
+            };
+            match self.add_ons.get_mut(&(caller, plan_index)) {
+                Some(list) => list.push(add_on),
+                None => {
+                    self.add_ons.insert((caller, plan_index), vec![add_on]);
+                }
+            }
+        }
+
+        /// Disabling previously created add-ons of `plan_index` of the `caller`
+        ///
+        /// # Note
+        ///
+        /// Purchased add-ons stay valid until the end of their subscription, but disabled add-ons
+        /// can not be purchased and are not renewed.
+        ///
+        /// # Panics
+        /// If `add_on_index` is bigger than the length of add-ons of the plan
+        ///
+        /// # Examples
+        /// Examples in `add_on_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn change_add_on_disable(&mut self, plan_index: u128, add_on_index: u128) {
+            let caller: AccountId = self.env().caller();
+            let number: usize = add_on_index.try_into().unwrap();
+            match self.add_ons.get_mut(&(caller, plan_index)) {
+                Some(list) if list.len() > number => list[number].disabled = !list[number].disabled,
+                _ => panic!("Wrong add-on index!"),
+            }
+        }
+
+        /// Purchasing `add_on_index` of `plan_index` of `provider_address` on top of the active
+        /// subscription of `caller`
+        ///
+        /// The add-on ends with the subscription and costs its `price` prorated to the remaining time
+        /// of the subscription. Like `subscribe`, (`plan.max_refund_permille_policy` * `add-on price`) / 1000
+        /// will be locked in the `Linked List` and the rest is sent to the provider. Any paid amount
+        /// above the add-on price is sent back to `caller`. The add-on is renewed and refunded
+        /// alongside the subscription.
+        ///
+        /// # Panics
+        /// If `caller` has no active subscription to `plan_index` of `provider_address`
+        /// If the add-on does not exist, is disabled or is already purchased
+        /// If paid amount is less than the prorated price of the add-on
+        ///
+        /// # Emits
+        /// AddOnPurchaseEvent
+        ///
+        /// # Examples
+        /// Examples in `add_on_works` in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn purchase_add_on(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            add_on_index: u128,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+
+            let (plan, periods, end_time): (PlanConsts, u64, u64) =
+                match self.get_active_record(caller, provider_address, plan_index) {
+                    Some(record) => (
+                        record.plan,
+                        record.periods,
+                        record
+                            .subscription_time
+                            .saturating_add(record.plan.duration),
+                    ),
+                    None => {
+                        panic!("You should have been subscribed to this plan for adding add-ons!")
+                    }
+                };
+            let record_index: u128 = *self
+                .plan_index_to_record_index
+                .get(&(caller, provider_address, plan_index))
+                .unwrap();
+            let add_on: AddOn = match self.add_ons.get(&(provider_address, plan_index)) {
+                Some(list) if list.len() > add_on_index.try_into().unwrap() => {
+                    list[add_on_index as usize].clone()
+                }
+                _ => panic!("Wrong add-on index!"),
+            };
+            assert!(!add_on.disabled, "Add-on is currently disabled by provider");
+            assert!(
+                !self.has_add_on(caller, provider_address, plan_index, add_on_index),
+                "You already have this add-on!"
+            );
+
+            // a multi period subscription pays the add-on price for each of its periods
+            let price: u128 = Self::prorated_price(
+                &PlanConsts {
+                    price: add_on.price * u128::from(periods),
+                    ..plan
+                },
+                end_time,
+                time,
+            );
+            assert!(
+                self.env().transferred_balance() >= price,
+                "You have to pay the price of add-on"
+            );
+
+            let money_address: AccountId =
+                self.providers.get(&provider_address).unwrap().money_address;
+            assert_eq!(
+                self.pay_provider(
+                    provider_address,
+                    money_address,
+                    price * (1000 - plan.max_refund_permille_policy) / 1000
+                ),
+                Ok(())
+            );
+            if self.env().transferred_balance() > price {
+                assert_eq!(
+                    self.transfer(caller, self.env().transferred_balance() - price),
+                    Ok(())
+                );
+            }
+            // add-ons of lifetime plans are locked only for the refund window
+            let lock_end: u64 = end_time.min(time.saturating_add(self.refund_duration(
+                provider_address,
+                plan_index,
+                &plan,
+            )));
+            self.add_entry(
+                provider_address,
+                (lock_end - self.start_time) / 86400,
+                (price * plan.max_refund_permille_policy) / 1000,
+            );
+
+            let purchase: AddOnPurchase = AddOnPurchase {
+                add_on_index,
+                record_index,
+                start_time: time,
+                lock_end,
+                price,
+            };
+            match self
+                .add_on_purchases
+                .get_mut(&(caller, provider_address, plan_index))
+            {
+                Some(list) => {
+                    // add-ons of the previous subscriptions are expired
+                    list.retain(|x| x.record_index == record_index);
+                    list.push(purchase);
+                }
+                None => {
+                    self.add_on_purchases
+                        .insert((caller, provider_address, plan_index), vec![purchase]);
+                }
+            }
+
+            self.env().emit_event(AddOnPurchaseEvent {
+                provider: provider_address,
+                user_address: caller,
+                plan_index,
+                add_on_index,
+                price,
+            });
+        }
+
+        /// Setting the per unit price of `plan_index` of the `caller`
+        ///
+        /// A plan with a per unit price is a metered plan which users pay through their `Escrow`
+        /// by the usage reports of the provider. Setting `unit_price` to 0 makes the plan a
+        /// normal time based plan again.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        ///
+        /// # Examples
+        /// Examples in `report_usage_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_plan_unit_price(&mut self, plan_index: u128, unit_price: u128) {
+            let caller = self.env().caller();
+            let provider = match self.providers.get(&caller) {
+                Some(x) => x,
+                None => panic!("You should first register in the contract!"),
+            };
+            assert!(
+                provider.plan_count > plan_index,
+                "please select a valid plan"
+            );
+            if unit_price == 0 {
+                self.plan_unit_prices.take(&(caller, plan_index));
+            } else {
+                self.plan_unit_prices
+                    .insert((caller, plan_index), unit_price);
+            }
+        }
+
+        /// Setting the `meter` account which can report usage of metered plans on behalf of `caller`
+        /// and the `meter_key` which signs the usage reports of `caller`
+        ///
+        /// # Note
+        ///
+        /// `meter_key` is the 33 bytes compressed secp256k1 public key of the meter, reports of
+        /// `caller` are only accepted with a signature of this key, even if `caller` submits them.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `meter_key` is not 33 bytes
+        ///
+        /// # Examples
+        /// Examples in `report_usage_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_meter(&mut self, meter: AccountId, meter_key: Vec<u8>) {
+            let caller = self.env().caller();
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            assert_eq!(meter_key.len(), 33, "Invalid meter key!");
+            self.meters.insert(caller, meter);
+            self.meter_keys.insert(caller, meter_key);
+        }
+
+        /// Depositing paid amount to the `Escrow` of `caller` for metered plans of `provider_address`
+        ///
+        /// # Panics
+        /// If `provider` does not exist
+        ///
+        /// # Emits
+        /// EscrowDepositEvent
+        ///
+        /// # Examples
+        /// Examples in `report_usage_works` in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn deposit_escrow(&mut self, provider_address: AccountId) {
+            let caller: AccountId = self.env().caller();
+            let amount: u128 = self.env().transferred_balance();
+            assert!(
+                self.providers.contains_key(&provider_address),
+                "Provider not existed in the contract!"
+            );
+
+            match self.escrows.get_mut(&(caller, provider_address)) {
+                Some(escrow) => escrow.balance += amount,
+                None => {
+                    self.escrows.insert(
+                        (caller, provider_address),
+                        Escrow {
+                            balance: amount,
+                            spend_cap: 0,
+                            cap_period: 0,
+                            period_start: self.env().block_timestamp(),
+                            spent_in_period: 0,
+                            nonce: 0,
+                        },
+                    );
+                }
+            }
+
+            self.env().emit_event(EscrowDepositEvent {
+                provider: provider_address,
+                user_address: caller,
+                amount,
+            });
+        }
+
+        /// Withdrawing unused `amount` of the `Escrow` of `caller` for `provider_address`
+        ///
+        /// # Panics
+        /// If `caller` has no `Escrow` for `provider_address`
+        /// If `amount` is bigger than the escrow balance
+        ///
+        /// # Emits
+        /// EscrowWithdrawEvent
+        ///
+        /// # Examples
+        /// Examples in `report_usage_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn withdraw_escrow(&mut self, provider_address: AccountId, amount: u128) {
+            let caller: AccountId = self.env().caller();
+            let escrow: &mut Escrow = match self.escrows.get_mut(&(caller, provider_address)) {
+                Some(x) => x,
+                None => panic!("You have no escrow for this provider!"),
+            };
+            assert!(escrow.balance >= amount, "Insufficient escrow balance");
+            escrow.balance -= amount;
+            assert_eq!(self.transfer(caller, amount), Ok(()));
+
+            self.env().emit_event(EscrowWithdrawEvent {
+                provider: provider_address,
+                user_address: caller,
+                amount,
+            });
+        }
+
+        /// Setting the spend cap of the `Escrow` of `caller` for `provider_address`
+        ///
+        /// At most `spend_cap` can be debited by usage reports in each `cap_period` seconds,
+        /// `spend_cap` = 0 removes the cap.
+        ///
+        /// # Panics
+        /// If `caller` has no `Escrow` for `provider_address`
+        /// If `spend_cap` is not 0 and `cap_period` is 0
+        ///
+        /// # Examples
+        /// Examples in `report_usage_fails_spend_cap` and `set_spend_cap_fails_zero_period` in
+        /// `tests/test.rs`
+        #[ink(message)]
+        pub fn set_spend_cap(
+            &mut self,
+            provider_address: AccountId,
+            spend_cap: u128,
+            cap_period: u64,
+        ) {
+            assert!(spend_cap == 0 || cap_period > 0, "Cap period can not be 0!");
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+            let escrow: &mut Escrow = match self.escrows.get_mut(&(caller, provider_address)) {
+                Some(x) => x,
+                None => panic!("You have no escrow for this provider!"),
+            };
+            escrow.spend_cap = spend_cap;
+            escrow.cap_period = cap_period;
+            escrow.period_start = time;
+            escrow.spent_in_period = 0;
+        }
+
+        /// Reporting `units` used by `user` of metered `plan_index` of `provider_address`
+        ///
+        /// This function can be called by the provider or its meter account. It debits
+        /// `units` * unit price from the `Escrow` of `user` and sends it to the provider.
+        ///
+        /// # Note
+        ///
+        /// `nonce` has to be the number of previously accepted reports of this escrow, so
+        /// a report can not be submitted twice.
+        ///
+        /// `signature` is the 65 bytes recoverable ECDSA signature of the meter key of the
+        /// provider over the Blake2x256 hash of the SCALE encoded
+        /// `(user, provider_address, plan_index, units, nonce)`.
+        ///
+        /// # Panics
+        /// If `caller` is neither the provider nor its meter
+        /// If plan is not a metered plan or is `disabled`
+        /// If provider has not set a meter key or `signature` is not a signature of it
+        /// If `user` has no `Escrow` for `provider_address`
+        /// If `nonce` is not the next nonce of the escrow
+        /// If the cost overflows or exceeds the spend cap of the current period or the escrow
+        /// balance
+        ///
+        /// # Emits
+        /// UsageReportEvent
+        ///
+        /// # Examples
+        /// Examples in `report_usage_works`, `report_usage_fails_spend_cap`,
+        /// `report_usage_fails_invalid_signature` and `report_usage_fails_cost_overflow` in
+        /// `tests/test.rs`
+        #[ink(message)]
+        pub fn report_usage(
+            &mut self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            units: u128,
+            nonce: u64,
+            signature: Vec<u8>,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+            assert!(
+                caller == provider_address || self.meters.get(&provider_address) == Some(&caller),
+                "You are not allowed to report usage for this provider!"
+            );
+            let unit_price: u128 = match self.plan_unit_prices.get(&(provider_address, plan_index))
+            {
+                Some(x) => *x,
+                None => panic!("This plan is not a metered plan!"),
+            };
+            assert!(
+                !self
+                    .plans
+                    .get(&(provider_address, plan_index))
+                    .unwrap()
+                    .disabled,
+                "Plan is currently disabled by provider"
+            );
+            let meter_key: &Vec<u8> = match self.meter_keys.get(&provider_address) {
+                Some(x) => x,
+                None => panic!("Provider has no meter key!"),
+            };
+            let signature: [u8; 65] = match signature.as_slice().try_into() {
+                Ok(x) => x,
+                Err(_) => panic!("Usage report is not signed by the meter!"),
+            };
+            let message_hash =
+                Self::usage_report_hash(user, provider_address, plan_index, units, nonce);
+            assert!(
+                super::ecdsa::recover(&signature, &message_hash)
+                    .as_ref()
+                    .map(|key| &key[..])
+                    == Some(&meter_key[..]),
+                "Usage report is not signed by the meter!"
+            );
+            let money_address: AccountId =
+                self.providers.get(&provider_address).unwrap().money_address;
+
+            let escrow: &mut Escrow = match self.escrows.get_mut(&(user, provider_address)) {
+                Some(x) => x,
+                None => panic!("User has no escrow for this provider!"),
+            };
+            assert_eq!(escrow.nonce, nonce, "Invalid usage report nonce");
+
+            let amount: u128 = units
+                .checked_mul(unit_price)
+                .expect("Usage cost overflows!");
+            if time >= escrow.period_start + escrow.cap_period {
+                escrow.period_start = time;
+                escrow.spent_in_period = 0;
+            }
+            assert!(
+                escrow.spend_cap == 0 || escrow.spent_in_period + amount <= escrow.spend_cap,
+                "Usage exceeds spend cap of user"
+            );
+            assert!(escrow.balance >= amount, "Insufficient escrow balance");
+
+            escrow.balance -= amount;
+            escrow.spent_in_period += amount;
+            escrow.nonce += 1;
+            assert_eq!(
+                self.pay_provider(provider_address, money_address, amount),
+                Ok(())
+            );
+
+            self.env().emit_event(UsageReportEvent {
+                provider: provider_address,
+                plan_index,
+                user_address: user,
+                units,
+                amount,
+                nonce,
+            });
+        }
+
+        /// Changing the username of `caller` to `new_username`
+        ///
+        /// # Note
+        ///
+        /// The old username is released and can not be claimed by any account, including
+        /// `caller`, until `username_release_cooldown` is passed.
+        ///
+        /// # Panics
+        /// If `caller` has no username
+        /// If `new_username` is not available
+        ///
+        /// # Examples
+        /// Examples in `change_username_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn change_username(&mut self, new_username: String) {
+            let caller: AccountId = self.env().caller();
+            let new_username: String = Self::normalize_username(&new_username);
+            assert!(
+                self.address_to_username.contains_key(&caller),
+                "this address has not a valid associated username!"
+            );
+            assert_eq!(
+                self.username_status(&new_username),
+                UsernameStatus::Available,
+                "username is invalid!"
+            );
+            self.release_username_of(caller);
+            self.address_to_username
+                .insert(caller, new_username.clone());
+            self.username_to_address.insert(new_username, caller);
+        }
+
+        /// Releasing the username of `caller`
+        ///
+        /// # Note
+        ///
+        /// The username can not be claimed by any account, including `caller`, until
+        /// `username_release_cooldown` is passed.
+        ///
+        /// # Panics
+        /// If `caller` has no username
+        ///
+        /// # Examples
+        /// Examples in `release_username_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn release_username(&mut self) {
+            let caller: AccountId = self.env().caller();
+            assert!(
+                self.address_to_username.contains_key(&caller),
+                "this address has not a valid associated username!"
+            );
+            self.release_username_of(caller);
+        }
+
+        /// Starting the transfer of username of `caller` to `to`
+        ///
+        /// The username will be moved when `to` calls `accept_username_transfer`. Calling this
+        /// function again replaces the pending transfer.
+        ///
+        /// # Panics
+        /// If `caller` has no username
+        /// If `to` already has a username
+        ///
+        /// # Examples
+        /// Examples in `transfer_username_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn transfer_username(&mut self, to: AccountId) {
+            let caller: AccountId = self.env().caller();
+            assert!(
+                self.address_to_username.contains_key(&caller),
+                "this address has not a valid associated username!"
+            );
+            assert!(
+                !self.address_to_username.contains_key(&to),
+                "Receiver already has a username!"
+            );
+            self.username_transfers.insert(caller, to);
+        }
+
+        /// Canceling the pending username transfer of `caller`
+        ///
+        /// # Panics
+        /// If `caller` has no pending username transfer
+        #[ink(message)]
+        pub fn cancel_username_transfer(&mut self) {
+            assert!(
+                self.username_transfers.take(&self.env().caller()).is_some(),
+                "There is no pending username transfer!"
+            );
+        }
+
+        /// Accepting the username that `from` is transferring to `caller`
+        ///
+        /// # Panics
+        /// If `from` has no pending username transfer to `caller`
+        /// If `caller` already has a username
+        ///
+        /// # Examples
+        /// Examples in `transfer_username_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn accept_username_transfer(&mut self, from: AccountId) {
+            let caller: AccountId = self.env().caller();
+            assert_eq!(
+                self.username_transfers.get(&from),
+                Some(&caller),
+                "There is no pending username transfer!"
+            );
+            assert!(
+                !self.address_to_username.contains_key(&caller),
+                "Receiver already has a username!"
+            );
+            self.username_transfers.take(&from);
+            let username: String = self.address_to_username.take(&from).unwrap();
+            self.address_to_username.insert(caller, username.clone());
+            self.username_to_address.insert(username, caller);
+        }
+
+        /// Setting the `subscrypt_pass_hash` of caller to `pass`
+        ///
+        /// # Note
+        ///
+        /// The `subscrypt_pass_hash` will also be set in `subscribe` function in first subscription
+        ///
+        ///
+        /// # Panics
+        /// If `caller` does not exist in `users`
+        #[ink(message)]
+        pub fn set_user_subscrypt_pass(&mut self, pass: [u8; 32]) {
+            match self.users.get_mut(&self.env().caller()) {
+                Some(x) => x.subscrypt_pass_hash = pass,
+                None => panic!("User doesn't exist!"),
+            };
+        }
+
+        /// Setting the `pass_hash` of caller in specific provider to `pass`
+        ///
+        /// # Panics
+        /// If `caller` does not exist in `users`
+        #[ink(message)]
+        pub fn subs_crypt_pass_hash_for_each_provider(
+            &mut self,
+            provider_address: AccountId,
+            pass: [u8; 32],
+        ) {
+            match self
+                .records
+                .get_mut(&(self.env().caller(), provider_address))
+            {
+                Some(x) => x.pass_hash = pass,
+                None => panic!("User doesn't exist!"),
+            };
+        }
+        /// Setting the public key that users encrypt their characteristics values of `caller` with
+        ///
+        /// Each call rotates the key and increases its version. Subscription records keep the
+        /// version that was current when they were created.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `public_key` is empty
+        ///
+        /// # Emits
+        /// `EncryptionKeyChangeEvent`
+        ///
+        /// # Examples
+        /// Examples in `set_encryption_key_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_encryption_key(&mut self, algorithm: KeyAlgorithm, public_key: Vec<u8>) {
+            let caller: AccountId = self.env().caller();
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            assert!(!public_key.is_empty(), "Public key can not be empty!");
+            let version: u32 = self.encryption_key_version(caller) + 1;
+            self.encryption_keys.insert(
+                caller,
+                EncryptionKey {
+                    algorithm,
+                    public_key: public_key.clone(),
+                    version,
+                },
+            );
+            self.env().emit_event(EncryptionKeyChangeEvent {
+                provider: caller,
+                algorithm,
+                public_key,
+                version,
+            });
+        }
+
+        /// Setting the payees that every payout of `caller` will be split between
+        ///
+        /// Each payee receives `share_permille` / 1000 of every payout that would otherwise go to
+        /// `money_address` (or to the provider itself in `withdraw`). Passing empty vectors removes
+        /// the payees.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If length of `addresses` and `shares_permille` are not the same
+        /// If sum of `shares_permille` is not 1000
+        ///
+        /// # Examples
+        /// Examples in `set_payees_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_payees(&mut self, addresses: Vec<AccountId>, shares_permille: Vec<u128>) {
+            let caller = self.env().caller();
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            assert_eq!(
+                addresses.len(),
+                shares_permille.len(),
+                "Wrong Number of Args"
+            );
+            if addresses.is_empty() {
+                self.payees.take(&caller);
+                return;
+            }
+            assert_eq!(
+                shares_permille.iter().sum::<u128>(),
+                1000,
+                "Sum of shares should be 1000"
+            );
+
+            let payees: Vec<Payee> = addresses
+                .iter()
+                .zip(shares_permille.iter())
+                .map(|(address, share_permille)| Payee {
+                    address: *address,
+                    share_permille: *share_permille,
+                })
+                .collect();
+            self.payees.insert(caller, payees);
+        }
+
+        /// Registering `affiliate` for `caller` with `commission_permille` of provider portion of
+        /// each subscription that `affiliate` refers
+        ///
+        /// # Note
+        ///
+        /// If `affiliate` is already registered only its commission is changed, which does not
+        /// effect the subscriptions that were referred before.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `commission_permille` is bigger than 1000
+        ///
+        /// # Examples
+        /// Examples in `subscribe_works_with_referrer` in `tests/test.rs`
+        #[ink(message)]
+        pub fn register_affiliate(&mut self, affiliate: AccountId, commission_permille: u128) {
+            let caller = self.env().caller();
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            assert!(
+                commission_permille <= 1000,
+                "Commission can not be more than 1000"
+            );
+            match self.affiliates.get_mut(&(caller, affiliate)) {
+                Some(x) => x.commission_permille = commission_permille,
+                None => {
+                    self.affiliates.insert(
+                        (caller, affiliate),
+                        Affiliate {
+                            commission_permille,
+                            earned: 0,
+                            withdrawable: 0,
+                        },
+                    );
+                }
+            }
+        }
+
+        /// `affiliate` can use this function to collect her commissions from referring users to
+        /// `provider_address`
+        ///
+        /// # Returns
+        /// `paid` amount is returned
+        ///
+        /// # Panics
+        /// If `caller` is not an affiliate of `provider_address`
+        ///
+        /// # Examples
+        /// Examples in `subscribe_works_with_referrer` in `tests/test.rs`
+        #[ink(message)]
+        pub fn withdraw_affiliate_earnings(&mut self, provider_address: AccountId) -> u128 {
+            let caller: AccountId = self.env().caller();
+            let affiliate: &mut Affiliate =
+                match self.affiliates.get_mut(&(provider_address, caller)) {
+                    Some(x) => x,
+                    None => panic!("You are not an affiliate of this provider!"),
+                };
+            let amount: u128 = affiliate.withdrawable;
+            affiliate.withdrawable = 0;
+            if amount > 0 {
+                assert_eq!(self.transfer(caller, amount), Ok(()));
+            }
+            amount
+        }
+
+        /// Setting the `subscrypt_pass_hash` of caller to `pass`
+        ///
+        /// # Note
+        ///
+        /// The `subscrypt_pass_hash` will also be set in `provider_register` function in first subscription
+        ///
+        ///
+        /// # Panics
+        /// If `caller` does not exist in `providers`
+        #[ink(message)]
+        pub fn set_provider_subscrypt_pass(&mut self, pass: [u8; 32]) {
+            match self.providers.get_mut(&self.env().caller()) {
+                Some(x) => x.subscrypt_pass_hash = pass,
+                None => panic!("User doesn't exist!"),
+            };
+        }
+
+        /// This function is used when providers want to collect the locked money for refund policy
+        ///
+        /// In this function, we will unlock that money which was locked in `subscribe` function via the
+        /// LinkedList mechanism, so providers can `withdraw` them when the due date passed.
+        /// The platform commission (`platform_fee_basis_points`) is deducted from the paid amount.
+        ///
+        /// # Returns
+        /// `paid` amount is returned
+        ///
+        /// # Panics
+        /// If `provider` does not exist
+        ///
+        /// # Examples
+        /// Examples in `withdraw_works` and `withdraw_works2` in `tests/test.rs`
+        #[ink(message)]
+        pub fn withdraw(&mut self) -> u128 {
+            assert!(
+                self.providers.contains_key(&self.env().caller()),
+                "You are not a registered provider"
+            );
+
+            let caller: AccountId = self.env().caller();
+            let t = self.process(caller, self.env().block_timestamp() / 86400);
+            if t.withdrawing_amount > 0 {
+                assert_eq!(
+                    self.pay_provider(caller, caller, t.withdrawing_amount),
+                    Ok(())
+                );
+            }
+
+            let linked_list: &mut LinkedList =
+                &mut self.providers.get_mut(&caller).unwrap().payment_manager;
+
+            linked_list.length -= t.reduced_length;
+            linked_list.head = t.current_linked_list_head;
+
+            return t.withdrawing_amount;
+        }
+
+        /// `user` can use this function to update the characteristics values of her active
+        /// subscription without waiting for `renew`
+        ///
+        /// # Note
+        ///
+        /// The record is marked with the current key version of provider, so values have to be
+        /// encrypted with the current `EncryptionKey`.
+        ///
+        /// # Panics
+        /// If `caller` has no active subscription for `plan_index` of `provider_address`
+        /// If number of `characteristics_values_encrypted` is not equal to plan characteristics
+        ///
+        /// # Emits
+        /// `CharacteristicsUpdateEvent`
+        ///
+        /// # Examples
+        /// Examples in `update_characteristics_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn update_characteristics(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            characteristics_values_encrypted: Vec<String>,
+        ) {
+            let caller: AccountId = self.env().caller();
+            assert!(
+                self.get_active_record(caller, provider_address, plan_index)
+                    .is_some(),
+                "You should have been subscribed to this plan for update!"
+            );
+            assert_eq!(
+                characteristics_values_encrypted.len(),
+                self.plans_characteristics
+                    .get(&(provider_address, plan_index))
+                    .unwrap()
+                    .len(),
+                "invalid characteristic values!"
+            );
+            self.validate_characteristics(
+                provider_address,
+                plan_index,
+                &characteristics_values_encrypted,
+            );
+            let key_version: u32 = self.encryption_key_version(provider_address);
+            let schema_version: u32 = self.schema_version(provider_address, plan_index);
+            let record_index: u128 = *self
+                .plan_index_to_record_index
+                .get(&(caller, provider_address, plan_index))
+                .unwrap();
+            let record: &mut SubscriptionRecord = self
+                .subscription_records
+                .get_mut(&(caller, provider_address, record_index))
+                .unwrap();
+            record.characteristics_values_encrypted = characteristics_values_encrypted.clone();
+            record.key_version = key_version;
+            record.schema_version = schema_version;
+            record.characteristics_version += 1;
+            let version: u32 = record.characteristics_version;
+
+            self.env().emit_event(CharacteristicsUpdateEvent {
+                provider: provider_address,
+                user_address: caller,
+                plan_index,
+                characteristics_values_encrypted,
+                version,
+            });
+        }
+
+        /// `users` can use this function to easily refund their subscription as the policy of that
+        /// specific plan was set. The `users` will be paid back at most
+        /// (`plan.max_refund_permille_policy` * `transferred_balance`) / 1000 and it will be linearly
+        /// decreased as time passed and will get to 0. The `provider` will get 0 at least and will linearly
+        /// get more if `user` refund later. If the subscription is a part of a bundle, all records of
+        /// the bundle are refunded together like `refund_bundle`.
+        ///
+        /// # Returns
+        /// `paid` amount is returned
+        ///
+        /// # Panics
+        /// If `provider` does not exist
+        ///
+        /// # Examples
+        /// Assume that `plan.max_refund_permille_policy` = 500 and `plan.price` = 100 the duration
+        /// of the plan is a month(30 days month). if `user` refund in first half of the month, then the user will
+        /// be paid 50. if `user` refund in day 20th of month then `user` will be paid 33.33 and `provider`
+        /// will be paid 16.66.
+        /// Other Examples in `refund_works`, `refund_works2` and `subscribe_works_with_referrer` in `tests/test.rs`
+        #[ink(message)]
+        pub fn refund(&mut self, provider_address: AccountId, plan_index: u128) -> u128 {
+            let caller: AccountId = self.env().caller();
+            let bundle_index: Option<u128> =
+                match self.get_active_record(caller, provider_address, plan_index) {
+                    Some(record) => {
+                        assert!(
+                            !record.complimentary,
+                            "Complimentary subscriptions can not be refunded!"
+                        );
+                        record.bundle_index
+                    }
+                    None => panic!("You are not in this plan or already refunded"),
+                };
+            match bundle_index {
+                Some(bundle_index) => self.refund_bundle_records(caller, bundle_index),
+                None => self.refund_record(caller, provider_address, plan_index),
+            }
+        }
+
+        /// Subscribing to `periods` consecutive durations of `plan_index` of the `provider_address`
+        /// at once
+        ///
+        /// The price is `periods` * `price` of the plan reduced by the discount tier of
+        /// `provider_address` for `periods`, which can be checked by `get_periods_price`. One
+        /// `SubscriptionRecord` covers all periods, the refundable part of each period is locked
+        /// until the end of that period and `refund` gives back the locked part of unused periods.
+        ///
+        /// # Note
+        ///
+        /// `renew` of the subscription is done for one period with the price of the plan, the
+        /// discount is not kept by grandfathering.
+        ///
+        /// # Panics
+        /// Same as `subscribe`
+        /// If `periods` is 0 or more than `MAX_PERIODS`
+        /// If the total duration of `periods` overflows
+        ///
+        /// # Emits
+        /// SubscribeEvent
+        ///
+        /// # Examples
+        /// Examples in `subscribe_periods_works` and `subscribe_periods_renew_works` in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn subscribe_periods(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            pass: [u8; 32],
+            username: String,
+            characteristics_values_encrypted: Vec<String>,
+            periods: u64,
+        ) {
+            assert!(
+                periods > 0 && periods <= MAX_PERIODS,
+                "Wrong number of periods!"
+            );
+            self.subscribe_plan(
+                provider_address,
+                plan_index,
+                pass,
+                username,
+                characteristics_values_encrypted,
+                SubscribeOptions {
+                    referrer: None,
+                    periods,
+                },
+            );
+        }
+
+        /// Creating a bundle of plans of partner providers which is sold at one `price`
+        ///
+        /// # Note
+        ///
+        /// `caller` has to have a plan in the bundle and accepts the bundle on creation, other
+        /// providers of the bundle have to accept it by `accept_bundle` before anyone can subscribe.
+        /// Each provider is paid `shares_permille` of the price of each subscription.
+        ///
+        /// # Returns
+        /// index of the created bundle
+        ///
+        /// # Panics
+        /// If the caller is not a valid provider or has no plan in the bundle
+        /// If `plans` is not the same length as `shares_permille`
+        /// If sum of `shares_permille` is not 1000
+        /// If a plan is repeated or does not exist
+        ///
+        /// # Examples
+        /// Examples in `bundle_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn create_bundle(
+            &mut self,
+            plans: Vec<(AccountId, u128)>,
+            shares_permille: Vec<u128>,
+            duration: u64,
+            price: u128,
+            max_refund_permille_policy: u128,
+        ) -> u128 {
+            let caller: AccountId = self.env().caller();
+            assert!(
+                self.providers.contains_key(&caller),
+                "You should first register in the contract!"
+            );
+            assert_eq!(plans.len(), shares_permille.len(), "Wrong Number of Args");
+            assert!(
+                duration > 0 && duration != LIFETIME_DURATION,
+                "Wrong bundle duration!"
+            );
+            assert!(max_refund_permille_policy <= 1000, "Wrong refund policy!");
+            assert_eq!(
+                shares_permille.iter().sum::<u128>(),
+                1000,
+                "Sum of shares should be 1000!"
+            );
+            assert!(
+                plans.iter().any(|x| x.0 == caller),
+                "You should have a plan in the bundle!"
+            );
+
+            let mut bundle_plans: Vec<BundlePlan> = Vec::new();
+            for i in 0..plans.len() {
+                let (provider_address, plan_index): (AccountId, u128) = plans[i];
+                assert!(
+                    !plans[..i].contains(&plans[i]),
+                    "Repeated plan in the bundle!"
+                );
+                let provider = match self.providers.get(&provider_address) {
+                    Some(provider) => provider,
+                    None => panic!("Provider not existed in the contract!"),
+                };
+                assert!(provider.plan_count > plan_index, "Wrong plan index!");
+                bundle_plans.push(BundlePlan {
+                    provider: provider_address,
+                    plan_index,
+                    share_permille: shares_permille[i],
+                    accepted: provider_address == caller,
+                });
+            }
+
+            let bundle_index: u128 = *self.bundle_count;
+            self.bundles.insert(
+                bundle_index,
+                Bundle {
+                    creator: caller,
+                    plans: bundle_plans,
+                    duration,
+                    price,
+                    max_refund_permille_policy,
+                },
+            );
+            *self.bundle_count += 1;
+            bundle_index
+        }
+
+        /// Accepting to take part in the bundle with `bundle_index` by its providers
+        ///
+        /// # Panics
+        /// If the bundle does not exist
+        /// If the caller has no plan in the bundle
+        ///
+        /// # Examples
+        /// Examples in `bundle_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn accept_bundle(&mut self, bundle_index: u128) {
+            let caller: AccountId = self.env().caller();
+            let bundle: &mut Bundle = match self.bundles.get_mut(&bundle_index) {
+                Some(bundle) => bundle,
+                None => panic!("Bundle not existed in the contract!"),
+            };
+            let mut is_participant: bool = false;
+            for bundle_plan in bundle.plans.iter_mut() {
+                if bundle_plan.provider == caller {
+                    bundle_plan.accepted = true;
+                    is_participant = true;
+                }
+            }
+            assert!(is_participant, "You are not a provider of this bundle!");
+        }
+
+        /// Subscribing to all plans of the bundle with `bundle_index` at once
+        ///
+        /// # Note
+        ///
+        /// A `SubscriptionRecord` is created for each plan of the bundle with the bundle duration
+        /// and the share of the price of that provider, so `check_subscription` works for each of
+        /// them. `characteristics_values_encrypted` has the values of each plan in the order of
+        /// bundle plans. The records are refunded together by `refund_bundle` or `refund`.
+        ///
+        /// # Panics
+        /// If the bundle does not exist or is not accepted by all of its providers
+        /// If paid amount is not equal to the bundle price
+        /// If `caller` is already subscribed to one of the plans
+        /// Same as `subscribe` for each plan of the bundle
+        ///
+        /// # Emits
+        /// SubscribeEvent for each plan of the bundle
+        ///
+        /// # Examples
+        /// Examples in `bundle_works` in `tests/test.rs`
+        #[ink(message, payable)]
+        pub fn subscribe_bundle(
+            &mut self,
+            bundle_index: u128,
+            pass: [u8; 32],
+            username: String,
+            characteristics_values_encrypted: Vec<Vec<String>>,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let username: String = Self::normalize_username(&username);
+            let time: u64 = self.env().block_timestamp();
+            let bundle: Bundle = match self.bundles.get(&bundle_index) {
+                Some(bundle) => bundle.clone(),
+                None => panic!("Bundle not existed in the contract!"),
+            };
+            assert!(
+                bundle.plans.iter().all(|x| x.accepted),
+                "Bundle is not accepted by all providers!"
+            );
+            assert_eq!(
+                bundle.price,
+                self.env().transferred_balance(),
+                "You have to pay exact bundle price"
+            );
+            assert_eq!(
+                characteristics_values_encrypted.len(),
+                bundle.plans.len(),
+                "invalid characteristic values!"
+            );
+
+            for (bundle_plan, characteristics_values) in bundle
+                .plans
+                .iter()
+                .zip(characteristics_values_encrypted.iter())
+            {
+                let provider_address: AccountId = bundle_plan.provider;
+                let plan_index: u128 = bundle_plan.plan_index;
+                self.apply_scheduled_edit(provider_address, plan_index);
+                assert!(
+                    !self.check_subscription(caller, provider_address, plan_index),
+                    "You are already subscribed to this plan!"
+                );
+                assert!(
+                    !self
+                        .plans
+                        .get(&(provider_address, plan_index))
+                        .unwrap()
+                        .disabled,
+                    "Plan is currently disabled by provider"
+                );
+                assert_eq!(
+                    characteristics_values.len(),
+                    self.plans_characteristics
+                        .get(&(provider_address, plan_index))
+                        .unwrap()
+                        .len(),
+                    "invalid characteristic values!"
+                );
+                assert!(
+                    self.is_allowed_to_subscribe(provider_address, plan_index, caller),
+                    "You are not in the allowlist of this plan!"
+                );
+                self.validate_characteristics(provider_address, plan_index, characteristics_values);
+                self.take_capacity(provider_address, plan_index, caller);
+            }
+
+            if !self.address_to_username.contains_key(&caller) {
+                assert_eq!(
+                    self.username_status(&username),
+                    UsernameStatus::Available,
+                    "username is invalid!"
+                );
+                self.address_to_username.insert(caller, username.clone());
+                self.username_to_address.insert(username, caller);
+            }
+
+            let mut shared_price: u128 = 0;
+            for (i, characteristics_values) in
+                characteristics_values_encrypted.into_iter().enumerate()
+            {
+                let provider_address: AccountId = bundle.plans[i].provider;
+                let plan_index: u128 = bundle.plans[i].plan_index;
+                // the last provider gets the remainder of the price
+                let price: u128 = if i + 1 == bundle.plans.len() {
+                    bundle.price - shared_price
+                } else {
+                    bundle.price * bundle.plans[i].share_permille / 1000
+                };
+                shared_price += price;
+                let consts: PlanConsts = PlanConsts {
+                    duration: bundle.duration,
+                    price,
+                    max_refund_permille_policy: bundle.max_refund_permille_policy,
+                    disabled: false,
+                    kind: PlanKind::Recurring,
+                };
+
+                let money_address: AccountId =
+                    self.providers.get(&provider_address).unwrap().money_address;
+                assert_eq!(
+                    self.pay_provider(
+                        provider_address,
+                        money_address,
+                        price * (1000 - consts.max_refund_permille_policy) / 1000
+                    ),
+                    Ok(())
+                );
+                self.add_entry(
+                    provider_address,
+                    (time + consts.duration - self.start_time) / 86400,
+                    Self::period_promised_amount(&consts, 1, 0) / 1000,
+                );
+                self.set_active_subscription(
+                    provider_address,
+                    plan_index,
+                    caller,
+                    time + consts.duration,
+                );
+                self.add_plan_stats(provider_address, plan_index, false, price);
+                self.referrals.take(&(caller, provider_address, plan_index));
+
+                let subscription_record = SubscriptionRecord {
+                    provider: provider_address,
+                    plan: consts,
+                    plan_index,
+                    subscription_time: time,
+                    characteristics_values_encrypted: characteristics_values.clone(),
+                    key_version: self.encryption_key_version(provider_address),
+                    characteristics_version: 0,
+                    schema_version: self.schema_version(provider_address, plan_index),
+                    periods: 1,
+                    renewal_plan: consts,
+                    plan_epoch: self.plan_epoch(provider_address, plan_index),
+                    bundle_index: Some(bundle_index),
+                    complimentary: false,
+                    refunded: false,
+                };
+                self.push_record(caller, pass, subscription_record);
+
+                self.env().emit_event(SubscribeEvent {
+                    provider: provider_address,
+                    plan_index,
+                    user_address: caller,
+                    subscription_time: time,
+                    duration: consts.duration,
+                    price,
+                    characteristics: characteristics_values,
+                });
+            }
+        }
+
+        /// Refunding all records of the bundle subscription of `caller` together, each of them is
+        /// refunded like `refund`
+        ///
+        /// # Returns
+        /// sum of `paid` amounts is returned
+        ///
+        /// # Panics
+        /// If the bundle does not exist
+        /// If `caller` is not subscribed to the bundle or already refunded
+        ///
+        /// # Examples
+        /// Examples in `bundle_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn refund_bundle(&mut self, bundle_index: u128) -> u128 {
+            let caller: AccountId = self.env().caller();
+            self.refund_bundle_records(caller, bundle_index)
+        }
+
+        /// Providers can use this function to terminate the subscription of `user` to their
+        /// `plan_index`, for example because of fraud or shutting down the plan
+        ///
+        /// The subscription ends immediately and `user` is refunded like `refund`, so she is paid
+        /// at least the unused part of the locked amount of her subscription and its add-ons, which
+        /// is taken from the `Linked List` of the provider.
+        ///
+        /// # Note
+        ///
+        /// A subscription can be revoked at any time, but providers can not revoke it without
+        /// paying the refund of its unused part. A renewed subscription which is not started yet
+        /// is refunded completely. After the refund window of a lifetime subscription its locked
+        /// amount is already released, so only the add-ons that are still locked are refunded.
+        /// Complimentary subscriptions are revoked without any refund. `reason_code` is only used
+        /// in the event.
+        ///
+        /// # Returns
+        /// `paid` amount is returned
+        ///
+        /// # Panics
+        /// If `user` has no active subscription to `plan_index` of `caller`
+        /// If the subscription is a part of a bundle or is disputed
+        ///
+        /// # Emits
+        /// SubscriptionRevokeEvent
+        ///
+        /// # Examples
+        /// Examples in `revoke_subscription_works`, `revoke_subscription_works_after_renew` and
+        /// `revoke_subscription_works_after_refund_window` in `tests/test.rs`
+        #[ink(message)]
+        pub fn revoke_subscription(
+            &mut self,
+            user: AccountId,
+            plan_index: u128,
+            reason_code: u32,
+        ) -> u128 {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+            let record: &SubscriptionRecord = match self.get_active_record(user, caller, plan_index)
+            {
+                Some(record) => record,
+                None => panic!("User is not subscribed to this plan!"),
+            };
+            assert!(
+                record.bundle_index.is_none(),
+                "Bundle subscriptions can not be revoked!"
+            );
+            assert!(
+                !self.is_disputed(user, caller, plan_index),
+                "This subscription is disputed!"
+            );
+            let record_index: u128 = *self
+                .plan_index_to_record_index
+                .get(&(user, caller, plan_index))
+                .unwrap();
+            let refund_end: u64 = record
+                .subscription_time
+                .saturating_add(self.refund_duration(caller, plan_index, &record.plan));
+            let paid: u128 = if record.complimentary {
+                // complimentary subscriptions have no locked amount to refund
+                self.close_refunded_record(user, caller, plan_index, record_index, 0, 0);
+                0
+            } else if time >= refund_end {
+                let plan: PlanConsts = record.plan;
+                let paid: u128 = self.refund_add_ons(
+                    user,
+                    caller,
+                    plan_index,
+                    record_index,
+                    plan.max_refund_permille_policy,
+                );
+                self.close_refunded_record(user, caller, plan_index, record_index, 0, plan.price);
+                paid
+            } else {
+                self.refund_record(user, caller, plan_index)
+            };
+            self.env().emit_event(SubscriptionRevokeEvent {
+                provider: caller,
+                user_address: user,
+                plan_index,
+                reason_code,
+                paid,
+            });
+            paid
+        }
+
+        /// `users` can use this function to dispute their subscription, for example when the service
+        /// is never delivered
+        ///
+        /// The locked amount of the subscription is taken out of the `Linked List` and frozen, so
+        /// `withdraw` can not take it. The provider can settle the dispute by `settle_dispute` at
+        /// any time, otherwise after `dispute_response_window` the arbiter can decide it by
+        /// `resolve_dispute` in `dispute_arbitration_window`. If nobody closes the dispute in these
+        /// windows, anyone can close it by `expire_dispute` which pays the refund of the refund policy
+        /// at the time of opening the dispute. The subscription ends when the dispute is closed.
+        ///
+        /// # Note
+        ///
+        /// Add-ons of the subscription are not frozen.
+        ///
+        /// # Panics
+        /// If `caller` has no active subscription to `plan_index` of `provider_address`
+        /// If the subscription is complimentary or already disputed
+        /// If the refund window of the subscription is passed
+        ///
+        /// # Emits
+        /// DisputeEvent
+        ///
+        /// # Examples
+        /// Examples in `settle_dispute_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn open_dispute(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            reason_code: u32,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+            let record: &SubscriptionRecord =
+                match self.get_active_record(caller, provider_address, plan_index) {
+                    Some(record) => record,
+                    None => panic!("You are not in this plan or already refunded"),
+                };
+            assert!(
+                !record.complimentary,
+                "Complimentary subscriptions can not be disputed!"
+            );
+            let plan: PlanConsts = record.plan;
+            let subscription_time: u64 = record.subscription_time;
+            let periods: u64 = record.periods;
+            let record_index: u128 = *self
+                .plan_index_to_record_index
+                .get(&(caller, provider_address, plan_index))
+                .unwrap();
+            assert!(
+                !self.is_disputed(caller, provider_address, plan_index),
+                "This subscription is already disputed!"
+            );
+            let refund_duration: u64 = self.refund_duration(provider_address, plan_index, &plan);
+            let used_time: u64 = time.saturating_sub(subscription_time);
+            assert!(
+                used_time < refund_duration,
+                "Refund window of this subscription is passed!"
+            );
+
+            let frozen_amount: u128 = if periods > 1 {
+                self.release_period_locks(provider_address, &plan, subscription_time, periods)
+            } else {
+                let locked: u128 = plan.price * plan.max_refund_permille_policy / 1000;
+                self.remove_entry(
+                    provider_address,
+                    (subscription_time + refund_duration - self.start_time) / 86400,
+                    locked,
+                );
+                locked
+            };
+            let remaining: u128 = u128::from(refund_duration - used_time);
+            let policy_refund: u128 = core::cmp::min(
+                plan.price * remaining / u128::from(refund_duration),
+                frozen_amount,
+            );
+
+            self.disputes.insert(
+                (caller, provider_address, plan_index),
+                Dispute {
+                    record_index,
+                    reason_code,
+                    frozen_amount,
+                    policy_refund,
+                    opened_at: time,
+                    status: DisputeStatus::Open,
+                    user_amount: 0,
+                },
+            );
+            self.env().emit_event(DisputeEvent {
+                provider: provider_address,
+                user_address: caller,
+                plan_index,
+                status: DisputeStatus::Open,
+                user_amount: 0,
+            });
+        }
+
+        /// Providers can use this function to settle the open dispute of `user` on their
+        /// `plan_index` by agreeing to refund `user_amount` of the frozen amount
+        ///
+        /// The rest of the frozen amount is sent to the provider and the subscription ends.
+        ///
+        /// # Note
+        /// Providers can not offer less than the refund of the refund policy, a lower offer has to
+        /// be decided by the arbiter
+        ///
+        /// # Panics
+        /// If `user` has no open dispute on `plan_index` of `caller`
+        /// If `user_amount` is less than the refund policy or more than the frozen amount
+        ///
+        /// # Emits
+        /// DisputeEvent
+        ///
+        /// # Examples
+        /// Examples in `settle_dispute_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn settle_dispute(&mut self, user: AccountId, plan_index: u128, user_amount: u128) {
+            let caller: AccountId = self.env().caller();
+            let dispute: Dispute = self.open_dispute_of(user, caller, plan_index);
+            assert!(
+                user_amount >= dispute.policy_refund,
+                "User amount can not be less than the refund policy!"
+            );
+            self.close_dispute(
+                user,
+                caller,
+                plan_index,
+                DisputeStatus::Settled,
+                user_amount,
+            );
+        }
+
+        /// The arbiter can use this function to decide the open dispute of `user` on `plan_index`
+        /// of `provider_address` which is not settled in `dispute_response_window`
+        ///
+        /// `user_amount` of the frozen amount is sent to `user`, the rest is sent to the provider
+        /// and the subscription ends.
+        ///
+        /// # Panics
+        /// If `caller` is not `arbiter`
+        /// If `user` has no open dispute on `plan_index` of `provider_address`
+        /// If the time is not in the arbitration window of the dispute
+        /// If `user_amount` is more than the frozen amount
+        ///
+        /// # Emits
+        /// DisputeEvent
+        ///
+        /// # Examples
+        /// Examples in `resolve_dispute_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn resolve_dispute(
+            &mut self,
+            provider_address: AccountId,
+            user: AccountId,
+            plan_index: u128,
+            user_amount: u128,
+        ) {
+            assert_eq!(
+                self.env().caller(),
+                self.configs.arbiter,
+                "You are not the arbiter"
+            );
+            let time: u64 = self.env().block_timestamp();
+            let dispute: Dispute = self.open_dispute_of(user, provider_address, plan_index);
+            let response_end: u64 = dispute.opened_at + self.configs.dispute_response_window;
+            assert!(
+                time >= response_end
+                    && time < response_end + self.configs.dispute_arbitration_window,
+                "Dispute is not in its arbitration window!"
+            );
+            self.close_dispute(
+                user,
+                provider_address,
+                plan_index,
+                DisputeStatus::Resolved,
+                user_amount,
+            );
+        }
+
+        /// Anyone can use this function to close a dispute which is not settled or decided in its
+        /// windows, `user` is paid the refund of the refund policy at the time of opening the dispute
+        ///
+        /// # Panics
+        /// If `user` has no open dispute on `plan_index` of `provider_address`
+        /// If the arbitration window of the dispute is not passed
+        ///
+        /// # Emits
+        /// DisputeEvent
+        ///
+        /// # Examples
+        /// Examples in `expire_dispute_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn expire_dispute(
+            &mut self,
+            provider_address: AccountId,
+            user: AccountId,
+            plan_index: u128,
+        ) {
+            let time: u64 = self.env().block_timestamp();
+            let dispute: Dispute = self.open_dispute_of(user, provider_address, plan_index);
+            assert!(
+                time >= dispute.opened_at
+                    + self.configs.dispute_response_window
+                    + self.configs.dispute_arbitration_window,
+                "Dispute is not expired!"
+            );
+            self.close_dispute(
+                user,
+                provider_address,
+                plan_index,
+                DisputeStatus::Expired,
+                dispute.policy_refund,
+            );
+        }
+
+        /// Providers can use this function to grant a complimentary subscription of their
+        /// `plan_index` to `user` for `duration` without any payment
+        ///
+        /// # Note
+        ///
+        /// The granted `SubscriptionRecord` is flagged as complimentary, so it can not be refunded
+        /// or renewed and nothing is locked in the `Linked List`. It is counted separately from
+        /// paid subscriptions in `PlanStats` of the plan.
+        /// `user` has no pass for this grant, so `check_auth` and, if it is her first
+        /// subscription, `user_check_auth` fail for her until she sets her passes by
+        /// `subs_crypt_pass_hash_for_each_provider` and `set_user_subscrypt_pass`.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If `user` is already subscribed to the plan
+        /// If `duration` is 0 or characteristics are invalid
+        /// If the plan is full
+        ///
+        /// # Emits
+        /// SubscribeEvent
+        ///
+        /// # Examples
+        /// Examples in `grant_subscription_works` and `grant_subscription_check_auth_works` in
+        /// `tests/test.rs`
+        #[ink(message)]
+        pub fn grant_subscription(
+            &mut self,
+            user: AccountId,
+            plan_index: u128,
+            duration: u64,
+            characteristics_values_encrypted: Vec<String>,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+            let provider = match self.providers.get(&caller) {
+                Some(provider) => provider,
+                None => panic!("You should first register in the contract!"),
+            };
+            assert!(provider.plan_count > plan_index, "Wrong plan index!");
+            assert!(duration > 0, "Wrong duration!");
+            assert_eq!(
+                characteristics_values_encrypted.len(),
+                self.plans_characteristics
+                    .get(&(caller, plan_index))
+                    .unwrap()
+                    .len(),
+                "invalid characteristic values!"
+            );
+            assert!(
+                !self.check_subscription(user, caller, plan_index),
+                "User is already subscribed to this plan!"
+            );
+            self.validate_characteristics(caller, plan_index, &characteristics_values_encrypted);
+            self.take_capacity(caller, plan_index, user);
+            self.grant_record(
+                caller,
+                ImportedSubscription {
+                    user,
+                    plan_index,
+                    start_time: time,
+                    end_time: time.saturating_add(duration),
+                },
+                characteristics_values_encrypted,
+                true,
+            );
+        }
+
+        /// Providers can use this function to import their existing subscriptions from another
+        /// billing system with their remaining paid time
+        ///
+        /// # Note
+        ///
+        /// Each imported subscription creates a `SubscriptionRecord` from `start_time` to `end_time`
+        /// without any payment, which is complimentary if `complimentary` is true and is pre-paid
+        /// outside of the contract otherwise. Pre-paid records can be renewed with the current
+        /// configs of the plan, even if it is edited with grandfathering, and their refund pays
+        /// nothing, complimentary ones are like `grant_subscription`. Imported records have
+        /// no characteristics, users can set them by `update_characteristics`. At most
+        /// `MAX_IMPORT_BATCH_SIZE` subscriptions can be imported in each call, `chunk_import_csv`
+        /// can be used to split a CSV file of subscriptions to calls. Imported users have no pass
+        /// like the users of `grant_subscription`.
+        ///
+        /// # Panics
+        /// If `caller` is not a registered provider
+        /// If `subscriptions` is empty or longer than `MAX_IMPORT_BATCH_SIZE`
+        /// If a `plan_index` is bigger than the length of `plans` of `provider`
+        /// If `end_time` of a subscription is passed or is not after its `start_time`
+        /// If `start_time` of a subscription is before the start of the contract
+        /// If a user is already subscribed to the plan
+        /// If the plan is full
+        ///
+        /// # Emits
+        /// SubscribeEvent for each subscription
+        ///
+        /// # Examples
+        /// Examples in `import_subscriptions_works` and
+        /// `import_subscriptions_fails_start_before_contract` in `tests/test.rs`
+        #[ink(message)]
+        pub fn import_subscriptions(
+            &mut self,
+            subscriptions: Vec<ImportedSubscription>,
+            complimentary: bool,
+        ) {
+            let caller: AccountId = self.env().caller();
+            let time: u64 = self.env().block_timestamp();
+            let plan_count: u128 = match self.providers.get(&caller) {
+                Some(provider) => provider.plan_count,
+                None => panic!("You should first register in the contract!"),
+            };
+            assert!(
+                !subscriptions.is_empty() && subscriptions.len() <= MAX_IMPORT_BATCH_SIZE,
+                "Wrong number of subscriptions!"
+            );
+            for subscription in subscriptions {
+                assert!(plan_count > subscription.plan_index, "Wrong plan index!");
+                assert!(
+                    subscription.start_time >= self.start_time
+                        && subscription.start_time < subscription.end_time
+                        && subscription.end_time > time,
+                    "Wrong subscription time!"
+                );
+                assert!(
+                    !self.check_subscription(subscription.user, caller, subscription.plan_index),
+                    "User is already subscribed to this plan!"
+                );
+                self.take_capacity(caller, subscription.plan_index, subscription.user);
+                self.grant_record(caller, subscription, Vec::new(), complimentary);
+            }
+        }
+
+        /// Setting the discounts of a plan of `caller` for subscribing to multiple periods at once
+        ///
+        /// # Note
+        ///
+        /// The biggest discount with `min_periods` less than or equal to the number of periods is
+        /// applied. Passing empty vectors removes the discounts.
+        ///
+        /// # Panics
+        /// If caller is not `provider`
+        /// If `plan_index` is bigger than the length of `plans` of `provider`
+        /// If length of `min_periods` and `discounts_permille` are not the same
+        /// If a discount is more than 1000
+        ///
+        /// # Examples
+        /// Examples in `subscribe_periods_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn set_discount_tiers(
+            &mut self,
+            plan_index: u128,
+            min_periods: Vec<u64>,
+            discounts_permille: Vec<u128>,
+        ) {
+            let caller = self.env().caller();
+            assert!(
+                plan_index < self.get_plan_length(caller),
+                "please select a valid plan"
+            );
+            assert_eq!(
+                min_periods.len(),
+                discounts_permille.len(),
+                "Wrong Number of Args"
+            );
+            let mut tiers: Vec<DiscountTier> = Vec::new();
+            for i in 0..min_periods.len() {
+                assert!(discounts_permille[i] <= 1000, "Wrong discount!");
+                tiers.push(DiscountTier {
+                    min_periods: min_periods[i],
+                    discount_permille: discounts_permille[i],
+                });
+            }
+            self.discount_tiers.insert((caller, plan_index), tiers);
+        }
+
+        /// This function returns the withdrawable amount
+        ///
+        /// # Returns
+        /// `paid` amount is returned
+        ///
+        /// # Panics
+        /// If `provider` does not exist
+        ///
+        /// # Examples
+        /// Examples in `withdraw_works` and `withdraw_works2` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_withdrawable_amount(&self) -> u128 {
+            assert!(
+                self.providers.contains_key(&self.env().caller()),
+                "You are not a registered provider"
+            );
+
+            let caller: AccountId = self.env().caller();
+            let t = self.process(caller, self.env().block_timestamp() / 86400);
+            t.withdrawing_amount
+        }
+
+        /// This function indicate if `user` can authenticate with given `pass_phrase`
+        /// # Note
+        /// `user` are encouraged to have different `pass_phrase` for each provider
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn check_auth(
+            &self,
+            user: AccountId,
+            provider: AccountId,
+            pass_phrase: String,
+        ) -> bool {
+            return match self.records.get(&(user, provider)) {
+                Some(record) => {
+                    let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
+                    return encoded == record.pass_hash;
+                }
+                None => false,
+            };
+        }
+
+        /// This function indicate if `username` can authenticate with given `pass_phrase`
+        /// # Note
+        /// `user` are encouraged to have different `pass_phrase` for each provider
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn check_auth_with_username(
+            &self,
+            username: String,
+            provider: AccountId,
+            pass_phrase: String,
+        ) -> bool {
+            let user = match self.username_address(&username) {
+                Some(name) => name,
+                None => panic!("this username is invalid!"),
+            };
+            self.check_auth(user, provider, pass_phrase)
+        }
+
+        /// This function indicate if `provider` can authenticate with given `pass_phrase`
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn provider_check_auth(&self, provider: AccountId, pass_phrase: String) -> bool {
+            return match self.providers.get(&provider) {
+                Some(provider) => {
+                    let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
+                    return encoded == provider.subscrypt_pass_hash;
+                }
+                None => false,
+            };
+        }
+
+        /// This function indicate if `provider` can authenticate with given `pass_phrase` and `username`
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn provider_check_auth_with_username(
+            &self,
+            username: String,
+            pass_phrase: String,
+        ) -> bool {
+            let address = match self.username_address(&username) {
+                Some(name) => name,
+                None => panic!("this username is invalid!"),
+            };
+            self.provider_check_auth(address, pass_phrase)
+        }
+
+        /// This function indicate if `user` can authenticate with given `pass_phrase`
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn user_check_auth(&self, user: AccountId, pass_phrase: String) -> bool {
+            return match self.users.get(&user) {
+                Some(user) => {
+                    let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
+                    return encoded == user.subscrypt_pass_hash;
+                }
+                None => false,
+            };
+        }
+
+        /// This function indicate if `user` can authenticate with given `pass_phrase` and `username`
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn user_check_auth_with_username(&self, username: String, pass_phrase: String) -> bool {
+            let address = match self.username_address(&username) {
+                Some(name) => name,
+                None => panic!("this username is invalid!"),
+            };
+            self.user_check_auth(address, pass_phrase)
+        }
+
+        /// This function indicate if `username` is available
+        ///
+        /// # Note
+        /// `username` is checked after case folding, the same as `subscribe` and `provider_register`.
+        /// a released username is not available, even for its previous owner, until
+        /// `username_release_cooldown` is passed
+        ///
+        /// # Returns
+        /// `UsernameStatus` is returned which is `Available` or the reason that `username` can not be claimed
+        ///
+        /// # Example
+        /// Examples in `is_username_available_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn is_username_available(&self, username: String) -> UsernameStatus {
+            self.username_status(&Self::normalize_username(&username))
+        }
+
+        /// This function returns collected platform commissions
+        #[ink(message)]
+        pub fn get_platform_balance(&self) -> u128 {
+            *self.platform_balance
+        }
+
+        /// We can get the payees of `provider_address` in this function
+        ///
+        /// # Returns
+        /// `Vec<Payee>` is returned which is empty if all payouts go to `money_address`
+        ///
+        /// # Example
+        /// Examples in `set_payees_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_payees(&self, provider_address: AccountId) -> Vec<Payee> {
+            match self.payees.get(&provider_address) {
+                Some(payees) => payees.clone(),
+                None => Vec::new(),
+            }
+        }
+
+        /// We can get the current `EncryptionKey` of `provider_address` in this function
+        ///
+        /// # Returns
+        /// `EncryptionKey` is returned
+        ///
+        /// # Example
+        /// Examples in `set_encryption_key_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_encryption_key(&self, provider_address: AccountId) -> EncryptionKey {
+            match self.encryption_keys.get(&provider_address) {
+                Some(key) => key.clone(),
+                None => panic!("Provider has no encryption key!"),
+            }
+        }
+
+        /// We can get `Affiliate` data of `affiliate` for `provider_address` in this function
+        ///
+        /// # Returns
+        /// `Affiliate` is returned
+        ///
+        /// # Example
+        /// Examples in `subscribe_works_with_referrer` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_affiliate(
+            &self,
+            provider_address: AccountId,
+            affiliate: AccountId,
+        ) -> Affiliate {
+            match self.affiliates.get(&(provider_address, affiliate)) {
+                Some(x) => *x,
+                None => panic!("This account is not an affiliate of this provider!"),
+            }
+        }
+
+        /// We can get the claw back of withdrawn commissions of `affiliate` which is not recovered
+        /// from her commissions yet in this function
+        ///
+        /// # Returns
+        /// a number is returned
+        ///
+        /// # Example
+        /// Examples in `refund_clawback_works_after_withdraw` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_affiliate_debt(
+            &self,
+            provider_address: AccountId,
+            affiliate: AccountId,
+        ) -> u128 {
+            match self.affiliate_debts.get(&(provider_address, affiliate)) {
+                Some(debt) => *debt,
+                None => 0,
+            }
+        }
+
+        /// This function returns provider money address
+        #[ink(message)]
+        pub fn get_money_address(&self) -> ink_env::AccountId {
+            let caller = self.env().caller();
+
+            let provider = match self.providers.get(&caller) {
+                Some(x) => x,
+                None => panic!("You should first register in the contract!"),
+            };
+
+            provider.money_address
+        }
+
+        /// This function indicate `username` of `caller`
+        ///
+        /// # Returns
+        /// `bool` is returned which shows the correctness of auth
+        ///
+        /// # Example
+        /// Examples in `check_auth_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_username(&self) -> String {
+            let caller: AccountId = self.env().caller();
+            match self.address_to_username.get(&caller) {
+                Some(username) => username.clone(),
+                None => panic!("this address has not a valid associated username!"),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_address_by_username(&self, username: String) -> AccountId {
+            match self.username_address(&username) {
+                Some(address) => address,
+                None => panic!("this username has not a valid associated address!"),
+            }
+        }
+
+        /// `user` can use this function to retrieve her whole subscription history to
+        /// different providers.
+        /// # Note
+        /// `user` has to provide their `pass_phrase` which will be used in SubsCrypt dashboard
+        ///
+        /// # Returns
+        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        ///
+        /// # Example
+        /// Examples in `retrieve_whole_data_with_password_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn retrieve_whole_data_with_username(
+            &self,
+            username: String,
+            pass_phrase: String,
+        ) -> Vec<SubscriptionRecord> {
+            let user = match self.username_address(&username) {
+                Some(name) => name,
+                None => panic!("this username is invalid!"),
+            };
+            let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
+            assert_eq!(
+                encoded,
+                self.users.get(&user).unwrap().subscrypt_pass_hash,
+                "Wrong auth"
+            );
+            self.retrieve_whole_data(user)
+        }
+
+        /// `user` can use this function to retrieve her whole subscription history to
+        /// different providers with their wallet.
+        ///
+        /// # Returns
+        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        ///
+        /// # Example
+        /// Examples in `retrieve_whole_data_with_wallet_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn retrieve_whole_data_with_wallet(&self) -> Vec<SubscriptionRecord> {
+            let caller: AccountId = self.env().caller();
+            self.retrieve_whole_data(caller)
+        }
+
+        /// `user` can use this function to retrieve her subscriptions history for a specific
+        /// provider.
+        ///
+        /// # Note
+        /// `user` has to provide their `pass_phrase` for that provider.
+        ///
+        /// # Returns
+        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        ///
+        /// # Example
+        /// Examples in `retrieve_data_with_password_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn retrieve_data_with_username(
+            &self,
+            username: String,
+            provider_address: AccountId,
+            pass_phrase: String,
+        ) -> Vec<SubscriptionRecord> {
+            let user = match self.username_address(&username) {
+                Some(name) => name,
+                None => panic!("this username is invalid!"),
+            };
+            let encoded = self.env().hash_encoded::<Sha2x256, _>(&pass_phrase);
+            assert_eq!(
+                encoded,
+                self.records
+                    .get(&(user, provider_address))
+                    .unwrap()
+                    .pass_hash,
+                "Wrong auth"
+            );
+            self.retrieve_data(user, provider_address)
+        }
+
+        /// `user` can use this function to retrieve her subscriptions history for a specific
+        /// provider with her wallet.
+        ///
+        /// # Returns
+        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
+        ///
+        /// # Example
+        /// Examples in `retrieve_data_with_password_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn retrieve_data_with_wallet(
+            &self,
+            provider_address: AccountId,
+        ) -> Vec<SubscriptionRecord> {
+            let caller: AccountId = self.env().caller();
+            self.retrieve_data(caller, provider_address)
+        }
+
+        /// We can get plan data in this function
+        ///
+        /// # Returns
+        /// `PlanConsts` is returned
+        ///
+        /// # Example
+        /// Examples in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_data(&self, provider_address: AccountId, plan_index: u128) -> PlanConsts {
+            assert!(
+                self.providers.contains_key(&provider_address),
+                "provider address is not valid!"
+            );
+            match self.effective_plan(provider_address, plan_index) {
+                Some(x) => x,
+                None => panic!("please select a valid plan"),
+            }
+        }
+
+        /// We can get the pending scheduled edit of a plan in this function
+        ///
+        /// # Returns
+        /// `Option<ScheduledEdit>` is returned which is `None` if there is no pending edit
+        ///
+        /// # Example
+        /// Examples in `schedule_plan_edit_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_scheduled_edit(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Option<ScheduledEdit> {
+            match self.scheduled_edits.get(&(provider_address, plan_index)) {
+                Some(edit) if edit.effective_time > self.env().block_timestamp() => Some(*edit),
+                _ => None,
+            }
+        }
+
+        /// We can get the refund window of a lifetime plan in this function
+        ///
+        /// # Returns
+        /// `Option<u64>` is returned which is `None` if plan is not a lifetime plan
+        ///
+        /// # Example
+        /// Examples in `lifetime_plan_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_lifetime_refund_window(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Option<u64> {
+            if self.get_plan_data(provider_address, plan_index).kind != PlanKind::Lifetime {
+                return None;
+            }
+            self.lifetime_refund_windows
+                .get(&(provider_address, plan_index))
+                .copied()
+        }
+
+        /// We can get a `Bundle` in this function
+        ///
+        /// # Returns
+        /// `Option<Bundle>` is returned which is `None` if the bundle does not exist
+        ///
+        /// # Example
+        /// Examples in `bundle_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_bundle(&self, bundle_index: u128) -> Option<Bundle> {
+            self.bundles.get(&bundle_index).cloned()
+        }
+
+        /// We can get the last `Dispute` of `user` on `plan_index` of `provider_address` in this
+        /// function
+        ///
+        /// # Returns
+        /// `Option<Dispute>` is returned which is `None` if `user` has never disputed the plan
+        ///
+        /// # Example
+        /// Examples in `settle_dispute_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_dispute(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Option<Dispute> {
+            self.disputes
+                .get(&(user, provider_address, plan_index))
+                .copied()
+        }
+
+        /// We can get `PlanStats` of a plan in this function
+        ///
+        /// # Returns
+        /// `PlanStats` is returned which is all zero if the plan has no subscription
+        ///
+        /// # Example
+        /// Examples in `grant_subscription_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_stats(&self, provider_address: AccountId, plan_index: u128) -> PlanStats {
+            match self.plan_stats.get(&(provider_address, plan_index)) {
+                Some(stats) => *stats,
+                None => PlanStats::default(),
+            }
+        }
+
+        /// We can get `AddOn` list of a plan in this function
+        ///
+        /// # Returns
+        /// `Vec<AddOn>` is returned which is empty if the plan has no add-on
+        ///
+        /// # Example
+        /// Examples in `add_on_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_add_ons(&self, provider_address: AccountId, plan_index: u128) -> Vec<AddOn> {
+            match self.add_ons.get(&(provider_address, plan_index)) {
+                Some(list) => list.clone(),
+                None => Vec::new(),
+            }
+        }
+
+        /// We can get `DiscountTier` list of a plan in this function
+        ///
+        /// # Returns
+        /// `Vec<DiscountTier>` is returned
+        ///
+        /// # Example
+        /// Examples in `subscribe_periods_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_discount_tiers(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Vec<DiscountTier> {
+            match self.discount_tiers.get(&(provider_address, plan_index)) {
+                Some(tiers) => tiers.clone(),
+                None => Vec::new(),
+            }
+        }
+
+        /// We can get the price of subscribing to `periods` periods of a plan at once in this function
+        ///
+        /// # Returns
+        /// a number is returned
+        ///
+        /// # Example
+        /// Examples in `subscribe_periods_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_periods_price(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            periods: u64,
+        ) -> u128 {
+            let price: u128 = self.get_plan_data(provider_address, plan_index).price;
+            self.periods_price(provider_address, plan_index, price, periods)
+        }
+
+        /// We can get number of seats of a plan in this function
+        ///
+        /// # Returns
+        /// a number is returned which is 1 for plans that are not team plans
+        ///
+        /// # Example
+        /// Examples in `add_seats_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_max_seats(&self, provider_address: AccountId, plan_index: u128) -> u128 {
+            *self
+                .plan_max_seats
+                .get(&(provider_address, plan_index))
+                .unwrap_or(&1)
+        }
+
+        /// We can get members that `admin` assigned to her team subscription in this function
+        ///
+        /// # Returns
+        /// `Vec<AccountId>` is returned
+        ///
+        /// # Example
+        /// Examples in `add_seats_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_seat_members(
+            &self,
+            admin: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Vec<AccountId> {
+            match self.seats.get(&(admin, provider_address, plan_index)) {
+                Some(members) => members.clone(),
+                None => Vec::new(),
+            }
+        }
+
+        /// We can get `Escrow` of `user` for `provider_address` in this function
+        ///
+        /// # Returns
+        /// `Escrow` is returned
+        ///
+        /// # Example
+        /// Examples in `report_usage_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_escrow(&self, user: AccountId, provider_address: AccountId) -> Escrow {
+            match self.escrows.get(&(user, provider_address)) {
+                Some(escrow) => *escrow,
+                None => panic!("User has no escrow for this provider!"),
+            }
+        }
+
+        /// get user's plan characteristics in this function
+        ///
+        /// # Returns
+        /// `user's plan characteristics` is returned
+        ///
+        /// # Example
+        /// Examples in `tests/test.rs` in get_user_plan_characteristics_works test
+        #[ink(message)]
+        pub fn get_user_plan_characteristics(&self, user: AccountId, provider_address: AccountId, plan_index: u128) -> Vec<String>  { // TODO: should add authentication
+            if !self
+                .plan_index_to_record_index
+                .contains_key(&(user, provider_address, plan_index))
+            {
+                panic!("user doesn't have this plan!");
+            }
+            let last_index: u128 = *self
+                .plan_index_to_record_index
+                .get(&(user, provider_address, plan_index))
+                .unwrap();
+            let record: &SubscriptionRecord = self
+                .subscription_records
+                .get(&(user, provider_address, last_index))
+                .unwrap();
+            if record.plan_index != plan_index
+                || record.refunded
+                || record
+                    .subscription_time
+                    .saturating_add(record.plan.duration)
+                    < self.env().block_timestamp()
+            {
+                panic!("user doesn't have this plan!");
+            }
+            record.characteristics_values_encrypted.clone()
+        }
+
+        /// We can get plan count in this function
+        ///
+        /// # Returns
+        /// a number is returned
+        ///
+        /// # Example
+        /// Examples in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_length(&self, provider_address: AccountId) -> u128 {
+            match self.providers.get(&provider_address) {
+                Some(provider) => provider.plan_count,
+                None => 0,
+            }
+        }
+
+        /// We can get `ProviderProfile` of `provider_address` in this function
+        ///
+        /// # Returns
+        /// `ProviderProfile` is returned which is empty if it is not set
+        ///
+        /// # Example
+        /// Examples in `list_providers_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_provider_profile(&self, provider_address: AccountId) -> ProviderProfile {
+            match self.provider_profiles.get(&provider_address) {
+                Some(profile) => profile.clone(),
+                None => ProviderProfile::default(),
+            }
+        }
+
+        /// We can list registered providers in order of registration in this function
+        ///
+        /// # Note
+        ///
+        /// At most `limit` providers with registration index of `start` or more are returned. If
+        /// `category` is set only providers of that category are returned. At most
+        /// `MAX_PROVIDER_SCAN` providers are scanned, so a page can have less than `limit`
+        /// providers while there are more providers to scan from `next_start`.
+        ///
+        /// # Returns
+        /// `ProviderPage` is returned
+        ///
+        /// # Example
+        /// Examples in `list_providers_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn list_providers(
+            &self,
+            category: Option<String>,
+            start: u32,
+            limit: u32,
+        ) -> ProviderPage {
+            let mut listings: Vec<ProviderListing> = Vec::new();
+            let mut index: u32 = start;
+            let end: u32 = core::cmp::min(
+                start.saturating_add(MAX_PROVIDER_SCAN),
+                self.provider_list.len(),
+            );
+            while index < end && listings.len() < limit as usize {
+                let provider: AccountId = self.provider_list[index];
+                let profile: ProviderProfile = self.get_provider_profile(provider);
+                if category.is_none() || category.as_ref() == Some(&profile.category) {
+                    listings.push(ProviderListing {
+                        registration_index: index,
+                        provider,
+                        username: match self.address_to_username.get(&provider) {
+                            Some(username) => username.clone(),
+                            None => String::new(),
+                        },
+                        profile,
+                    });
+                }
+                index += 1;
+            }
+            ProviderPage {
+                listings,
+                next_start: if index < self.provider_list.len() {
+                    Some(index)
+                } else {
+                    None
+                },
+            }
+        }
+
+        /// We can get `PlanCapacity` of a plan in this function
+        ///
+        /// # Returns
+        /// `PlanCapacity` is returned which has `max_active` = 0 if plan has no limit
+        ///
+        /// # Example
+        /// Examples in `waitlist_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_capacity(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> PlanCapacity {
+            match self.plan_capacities.get(&(provider_address, plan_index)) {
+                Some(capacity) => *capacity,
+                None => PlanCapacity::default(),
+            }
+        }
+
+        /// We can get number of active subscriptions of a plan in this function
+        ///
+        /// # Note
+        /// The expiries of at most `MAX_EXPIRY_DAYS` days after the last update of the count are
+        /// subtracted, `process_waitlist` processes the next days.
+        ///
+        /// # Returns
+        /// a number is returned
+        ///
+        /// # Example
+        /// Examples in `waitlist_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_active_count(&self, provider_address: AccountId, plan_index: u128) -> u128 {
+            match self.active_counts.get(&(provider_address, plan_index)) {
+                Some(active) => active.count - self.expired_count(provider_address, plan_index).0,
+                None => 0,
+            }
+        }
+
+        /// We can get the waitlist of a plan in this function
+        ///
+        /// # Returns
+        /// `Vec<AccountId>` is returned in order of joining
+        ///
+        /// # Example
+        /// Examples in `waitlist_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_waitlist(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Vec<AccountId> {
+            match self.waitlists.get(&(provider_address, plan_index)) {
+                Some(waitlist) => waitlist.clone(),
+                None => Vec::new(),
+            }
+        }
+
+        /// We can get the deadline that `user` can subscribe to a full plan until it in this function
+        ///
+        /// # Returns
+        /// `Option<u64>` is returned which is `None` if `user` has no offer
+        ///
+        /// # Example
+        /// Examples in `waitlist_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_waitlist_offer(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+        ) -> Option<u64> {
+            let offers = self.waitlist_offers.get(&(provider_address, plan_index))?;
+            offers
+                .iter()
+                .find(|x| x.user == user && x.deadline >= self.env().block_timestamp())
+                .map(|x| x.deadline)
+        }
+
+        /// We can check that `user` is in the allowlist of a plan in this function
+        ///
+        /// # Note
+        /// accounts that proved their membership in the current merkle root by
+        /// `submit_allowlist_proof` are included
+        ///
+        /// # Returns
+        /// `bool` is returned
+        ///
+        /// # Example
+        /// Examples in `allowlist_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn is_allowlisted(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+        ) -> bool {
+            match self
+                .plan_allowlists
+                .get(&(provider_address, plan_index, user))
+            {
+                Some(AllowlistEntry::Added) => true,
+                Some(AllowlistEntry::Proven(root)) => {
+                    self.get_allowlist_merkle_root(provider_address, plan_index) == Some(*root)
+                }
+                _ => false,
+            }
+        }
+
+        /// We can get merkle root of allowed accounts of a plan in this function
+        ///
+        /// # Returns
+        /// `Option<[u8; 32]>` is returned
+        ///
+        /// # Example
+        /// Examples in `submit_allowlist_proof_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_allowlist_merkle_root(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Option<[u8; 32]> {
+            self.plan_merkle_roots
+                .get(&(provider_address, plan_index))
+                .copied()
+        }
+
+        /// We can get `PlanMetadata` of a plan in this function
+        ///
+        /// # Returns
+        /// `PlanMetadata` is returned which is an empty public metadata if it is not set
+        ///
+        /// # Example
+        /// Examples in `get_provider_catalog_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_metadata(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> PlanMetadata {
+            match self.plan_metadata.get(&(provider_address, plan_index)) {
+                Some(metadata) => metadata.clone(),
+                None => PlanMetadata {
+                    name: String::new(),
+                    description: String::new(),
+                    uri: None,
+                    content_hash: None,
+                    visibility: Visibility::Public,
+                },
+            }
+        }
+
+        /// We can get all public plans of `provider_address` which are not disabled in this function
+        ///
+        /// # Returns
+        /// `Vec<CatalogPlan>` is returned which contains data, metadata and characteristics of plans,
+        /// the data is the same as `get_plan_data` so scheduled edits are shown after their
+        /// effective time
+        ///
+        /// # Example
+        /// Examples in `get_provider_catalog_works` and `schedule_plan_edit_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_provider_catalog(&self, provider_address: AccountId) -> Vec<CatalogPlan> {
+            let provider = match self.providers.get(&provider_address) {
+                Some(provider) => provider,
+                None => panic!("provider address is not valid!"),
+            };
+            let mut catalog: Vec<CatalogPlan> = Vec::new();
+            for plan_index in 0..provider.plan_count {
+                let plan: PlanConsts = self.effective_plan(provider_address, plan_index).unwrap();
+                let metadata: PlanMetadata = self.get_plan_metadata(provider_address, plan_index);
+                if plan.disabled || metadata.visibility != Visibility::Public {
+                    continue;
+                }
+                catalog.push(CatalogPlan {
+                    plan_index,
+                    plan,
+                    metadata,
+                    characteristics: self
+                        .plans_characteristics
+                        .get(&(provider_address, plan_index))
+                        .unwrap()
+                        .clone(),
+                });
+            }
+            catalog
+        }
+
+        /// We can get `CharacteristicSchema` of a plan in this function
+        ///
+        /// # Returns
+        /// `CharacteristicSchema` is returned which has empty `specs` if plan is not typed
+        ///
+        /// # Example
+        /// Examples in `set_characteristic_schema_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_characteristic_schema(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> CharacteristicSchema {
+            match self
+                .characteristic_schemas
+                .get(&(provider_address, plan_index))
+            {
+                Some(schema) => schema.clone(),
+                None => CharacteristicSchema::default(),
+            }
+        }
+
+        /// We can get plan characteristic keys in this function
+        ///
+        /// # Returns
+        /// `Vec<String>` is returned
+        ///
+        /// # Example
+        /// Examples in `tests/test.rs`
+        #[ink(message)]
+        pub fn get_plan_characteristics(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Vec<String> {
+            assert!(
+                self.providers.contains_key(&provider_address),
+                "index is not valid!"
+            );
+            match self
+                .plans_characteristics
+                .get(&(provider_address, plan_index))
+            {
+                Some(x) => x.clone(),
+                None => panic!("please select a valid plan"),
+            }
+        }
+
+        /// Get hash of String
+        ///
+        /// # Returns
+        /// `String` is returned
+        ///    
+        #[ink(message)]
+        pub fn get_sha2(&self, string: String) -> [u8; 32] {
+            let mut output = <Sha2x256 as HashOutput>::Type::default(); // 256-bit buffer
+            ink_env::hash_encoded::<Sha2x256, _>(&string, &mut output);
+            return output;
+        }
+
+        /// This function can be called to check if `user` has a valid subscription to the
+        /// specific `plan_index` of `provider`.
+        ///
+        /// # Note
+        /// if `user` refunded or her subscription is expired then this function will return false
+        /// if `user` is assigned a seat of a team subscription, the subscription of her seat admin is checked
+        ///
+        /// # Returns
+        /// `bool` which means if `user` is subscribed or not
+        ///
+        /// # Example
+        /// Examples in `check_subscription_works` and `add_seats_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn check_subscription(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> bool {
+            if self
+                .get_active_record(user, provider_address, plan_index)
+                .is_some()
+            {
+                return true;
+            }
+            match self.seat_admins.get(&(user, provider_address, plan_index)) {
+                Some(admin) => self
+                    .get_active_record(*admin, provider_address, plan_index)
+                    .is_some(),
+                None => false,
+            }
+        }
+
+        /// This function can be called to check if `user` has a valid subscription to the
+        /// specific `plan_index` of `provider` with `add_on_index` purchased on top of it.
+        ///
+        /// # Note
+        /// if `user` refunded or her subscription is expired then this function will return false
+        /// if `user` is assigned a seat of a team subscription, the add-ons of her seat admin are checked
+        ///
+        /// # Returns
+        /// `bool` which means if `user` has the add-on or not
+        ///
+        /// # Example
+        /// Examples in `add_on_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn check_subscription_with_add_on(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            add_on_index: u128,
+        ) -> bool {
+            if self
+                .get_active_record(user, provider_address, plan_index)
+                .is_some()
+            {
+                return self.has_add_on(user, provider_address, plan_index, add_on_index);
+            }
+            match self.seat_admins.get(&(user, provider_address, plan_index)) {
+                Some(admin) => {
+                    self.get_active_record(*admin, provider_address, plan_index)
+                        .is_some()
+                        && self.has_add_on(*admin, provider_address, plan_index, add_on_index)
+                }
+                None => false,
+            }
+        }
+
+        /// This function can be called to check if `user` has a valid subscription to the
+        /// specific `plan_index` of `provider`.
+        ///
+        /// # Note
+        /// if `user` refunded or her subscription is expired then this function will return false
+        ///
+        /// # Returns
+        /// `bool` which means if `user` is subscribed or not
+        ///
+        /// # Example
+        /// Examples in `check_subscription_works` in `tests/test.rs`
+        #[ink(message)]
+        pub fn check_subscription_with_username(
+            &self,
+            username: String,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> bool {
+            match self.username_address(&username) {
+                Some(name) => self.check_subscription(name, provider_address, plan_index),
+                None => false,
+            }
+        }
+
+        /// is_disputed : whether the last `SubscriptionRecord` of `user` for `plan_index` of
+        /// `provider_address` has an open dispute
+        fn is_disputed(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> bool {
+            match self.disputes.get(&(user, provider_address, plan_index)) {
+                Some(dispute) => dispute.status == DisputeStatus::Open,
+                None => false,
+            }
+        }
+
+        /// open_dispute_of : returns the open dispute of `user` on `plan_index` of `provider_address`
+        fn open_dispute_of(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Dispute {
+            match self.disputes.get(&(user, provider_address, plan_index)) {
+                Some(dispute) if dispute.status == DisputeStatus::Open => *dispute,
+                _ => panic!("There is no open dispute on this subscription!"),
+            }
+        }
+
+        /// close_dispute : pays `user_amount` of the frozen amount of an open dispute to `user` and
+        /// the rest to the provider, and ends the disputed subscription
+        fn close_dispute(
+            &mut self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            status: DisputeStatus,
+            user_amount: u128,
+        ) {
+            let dispute: &mut Dispute = self
+                .disputes
+                .get_mut(&(user, provider_address, plan_index))
+                .unwrap();
+            let frozen_amount: u128 = dispute.frozen_amount;
+            let record_index: u128 = dispute.record_index;
+            assert!(
+                user_amount <= frozen_amount,
+                "User amount can not be more than the frozen amount!"
+            );
+            dispute.status = status;
+            dispute.user_amount = user_amount;
+
+            if user_amount > 0 {
+                assert_eq!(self.transfer(user, user_amount), Ok(()));
+            }
+            if frozen_amount > user_amount {
+                assert_eq!(
+                    self.pay_provider(
+                        provider_address,
+                        self.providers.get(&provider_address).unwrap().money_address,
+                        frozen_amount - user_amount
+                    ),
+                    Ok(())
+                );
+            }
+            let price: u128 = self
+                .subscription_records
+                .get(&(user, provider_address, record_index))
+                .unwrap()
+                .plan
+                .price;
+            self.close_refunded_record(
+                user,
+                provider_address,
+                plan_index,
+                record_index,
+                user_amount,
+                price,
+            );
+            self.env().emit_event(DisputeEvent {
+                provider: provider_address,
+                user_address: user,
+                plan_index,
+                status,
+                user_amount,
+            });
+        }
+
+        /// add_plan_stats : counts a new subscription of a plan in its `PlanStats`
+        fn add_plan_stats(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            complimentary: bool,
+            price: u128,
+        ) {
+            if !self
+                .plan_stats
+                .contains_key(&(provider_address, plan_index))
+            {
+                self.plan_stats
+                    .insert((provider_address, plan_index), PlanStats::default());
+            }
+            let stats: &mut PlanStats = self
+                .plan_stats
+                .get_mut(&(provider_address, plan_index))
+                .unwrap();
+            if complimentary {
+                stats.complimentary_subscriptions += 1;
+            } else {
+                stats.paid_subscriptions += 1;
+            }
+            stats.revenue += price;
+        }
+
+        /// grant_record : adds a `SubscriptionRecord` of `subscription` to a plan of
+        /// `provider_address` which is not paid in the contract, it is either complimentary or
+        /// pre-paid outside of the contract. The zero pass hash that it sets for a new `User` or
+        /// `PlanRecord` matches no pass phrase, so `user` can not login until she sets her passes
+        fn grant_record(
+            &mut self,
+            provider_address: AccountId,
+            subscription: ImportedSubscription,
+            characteristics_values_encrypted: Vec<String>,
+            complimentary: bool,
+        ) {
+            let user: AccountId = subscription.user;
+            let plan_index: u128 = subscription.plan_index;
+            let start_time: u64 = subscription.start_time;
+            let duration: u64 = subscription.end_time - subscription.start_time;
+            let consts: PlanConsts = PlanConsts {
+                duration,
+                price: 0,
+                max_refund_permille_policy: 0,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            };
+            let subscription_record = SubscriptionRecord {
+                provider: provider_address,
+                plan: consts,
+                plan_index,
+                subscription_time: start_time,
+                characteristics_values_encrypted: characteristics_values_encrypted.clone(),
+                key_version: self.encryption_key_version(provider_address),
+                characteristics_version: 0,
+                schema_version: self.schema_version(provider_address, plan_index),
+                periods: 1,
+                renewal_plan: consts,
+                plan_epoch: self.plan_epoch(provider_address, plan_index),
+                bundle_index: None,
+                complimentary,
+                refunded: false,
+            };
+            self.push_record(user, [0; 32], subscription_record);
+            self.referrals.take(&(user, provider_address, plan_index));
+            self.set_active_subscription(provider_address, plan_index, user, subscription.end_time);
+            self.add_plan_stats(provider_address, plan_index, complimentary, 0);
+
+            self.env().emit_event(SubscribeEvent {
+                provider: provider_address,
+                plan_index,
+                user_address: user,
+                subscription_time: start_time,
+                duration,
+                price: 0,
+                characteristics: characteristics_values_encrypted,
+            });
+        }
+
+        /// has_add_on : whether `add_on_index` is purchased on top of the last `SubscriptionRecord`
+        /// of `user` for `plan_index` of `provider_address`
+        fn has_add_on(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            add_on_index: u128,
+        ) -> bool {
+            let record_index: u128 =
+                match self
+                    .plan_index_to_record_index
+                    .get(&(user, provider_address, plan_index))
+                {
+                    Some(index) => *index,
+                    None => return false,
+                };
+            match self
+                .add_on_purchases
+                .get(&(user, provider_address, plan_index))
+            {
+                Some(list) => list
+                    .iter()
+                    .any(|x| x.add_on_index == add_on_index && x.record_index == record_index),
+                None => false,
+            }
+        }
+
+        /// renewed_add_ons : (add_on_index, price) of the add-ons of the record of `user` with
+        /// `record_index` which are not disabled, so they are renewed alongside it
+        fn renewed_add_ons(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            record_index: u128,
+        ) -> Vec<(u128, u128)> {
+            let add_ons: &Vec<AddOn> = match self.add_ons.get(&(provider_address, plan_index)) {
+                Some(list) => list,
+                None => return Vec::new(),
+            };
+            match self
+                .add_on_purchases
+                .get(&(user, provider_address, plan_index))
+            {
+                Some(list) => list
+                    .iter()
+                    .filter(|x| x.record_index == record_index)
+                    .map(|x| x.add_on_index)
+                    .filter(|x| !add_ons[*x as usize].disabled)
+                    .map(|x| (x, add_ons[x as usize].price))
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+
+        /// refund_add_ons : refunds the add-ons of the record of `caller` with `record_index` like
+        /// `refund` and returns sum of the paid amounts
+        fn refund_add_ons(
+            &mut self,
+            caller: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            record_index: u128,
+            max_refund_permille_policy: u128,
+        ) -> u128 {
+            let time: u64 = self.env().block_timestamp();
+            let purchases: Vec<AddOnPurchase> = self
+                .add_on_purchases
+                .take(&(caller, provider_address, plan_index))
+                .unwrap_or_default();
+            let mut paid: u128 = 0;
+            // locks of the add-ons which are passed are already released to the provider
+            for purchase in purchases
+                .iter()
+                .filter(|x| x.record_index == record_index && x.lock_end > time)
+            {
+                let promised_amount: u128 = purchase.price * max_refund_permille_policy;
+                let price: u128 = purchase.price * 1000;
+                let duration: u128 = u128::from(purchase.lock_end - purchase.start_time).max(1);
+                let used: u128 =
+                    price * u128::from(time.saturating_sub(purchase.start_time)) / duration;
+                let customer_portion_locked_money: u128 =
+                    core::cmp::min(price.saturating_sub(used), promised_amount);
+                if promised_amount > customer_portion_locked_money {
+                    assert_eq!(
+                        self.pay_provider(
+                            provider_address,
+                            self.providers.get(&provider_address).unwrap().money_address,
+                            (promised_amount - customer_portion_locked_money) / 1000
+                        ),
+                        Ok(())
+                    );
+                }
+                assert_eq!(
+                    self.transfer(caller, customer_portion_locked_money / 1000),
+                    Ok(())
+                );
+                self.remove_entry(
+                    provider_address,
+                    (purchase.lock_end - self.start_time) / 86400,
+                    promised_amount / 1000,
+                );
+                paid += customer_portion_locked_money;
+            }
+            paid
+        }
+
+        /// encryption_key_version : current key version of `provider_address`, 0 if it has no key
+        fn encryption_key_version(&self, provider_address: AccountId) -> u32 {
+            match self.encryption_keys.get(&provider_address) {
+                Some(key) => key.version,
+                None => 0,
+            }
+        }
+
+        /// plan_from_v1 : converts a plan of the storage layout of version 1
+        fn plan_from_v1(plan: PlanConstsV1) -> PlanConsts {
+            PlanConsts {
+                duration: plan.duration,
+                price: plan.price,
+                max_refund_permille_policy: plan.max_refund_permille_policy,
+                disabled: plan.disabled,
+                kind: PlanKind::Recurring,
+            }
+        }
+
+        /// refund_duration : `duration` of `plan` which the refund of its subscriptions is calculated
+        /// by, it is the refund window for lifetime plans
+        fn refund_duration(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            plan: &PlanConsts,
+        ) -> u64 {
+            match plan.kind {
+                PlanKind::Recurring => plan.duration,
+                PlanKind::Lifetime => *self
+                    .lifetime_refund_windows
+                    .get(&(provider_address, plan_index))
+                    .unwrap(),
+            }
+        }
+
+        /// check_plan_kind : checks that `plan` is of `kind`, which is the kind of the edited plan,
+        /// and only `Lifetime` plans have `LIFETIME_DURATION`
+        fn check_plan_kind(plan: &PlanConsts, kind: PlanKind) {
+            assert!(plan.kind == kind, "Plan kind can not be changed!");
+            assert!(
+                (plan.duration == LIFETIME_DURATION) == (kind == PlanKind::Lifetime),
+                "Wrong plan duration!"
+            );
+        }
+
+        /// periods_price : `periods` * `price` reduced by the biggest applicable `DiscountTier`
+        fn periods_price(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            price: u128,
+            periods: u64,
+        ) -> u128 {
+            let mut discount_permille: u128 = 0;
+            if let Some(tiers) = self.discount_tiers.get(&(provider_address, plan_index)) {
+                for tier in tiers.iter() {
+                    if tier.min_periods <= periods && tier.discount_permille > discount_permille {
+                        discount_permille = tier.discount_permille;
+                    }
+                }
+            }
+            price * u128::from(periods) * (1000 - discount_permille) / 1000
+        }
+
+        /// period_promised_amount : refundable amount of `period` of a record with `plan` configs
+        /// which covers `periods` periods, multiplied by 1000
+        fn period_promised_amount(plan: &PlanConsts, periods: u64, period: u64) -> u128 {
+            let promised_amount: u128 = plan.price * plan.max_refund_permille_policy;
+            let periods: u128 = periods.into();
+            if u128::from(period) + 1 == periods {
+                promised_amount / periods + promised_amount % periods
+            } else {
+                promised_amount / periods
+            }
+        }
+
+        /// release_period_locks : removes the locked amounts of periods of a multi period record
+        /// that are not finished and returns their sum
+        fn release_period_locks(
+            &mut self,
+            provider_address: AccountId,
+            plan: &PlanConsts,
+            subscription_time: u64,
+            periods: u64,
+        ) -> u128 {
+            let time: u64 = self.env().block_timestamp();
+            let period_duration: u64 = plan.duration / periods;
+            let mut released: u128 = 0;
+            for period in (time - subscription_time) / period_duration..periods {
+                let locked: u128 = Self::period_promised_amount(plan, periods, period) / 1000;
+                self.remove_entry(
+                    provider_address,
+                    (subscription_time + (period + 1) * period_duration - self.start_time) / 86400,
+                    locked,
+                );
+                released += locked;
+            }
+            released
+        }
+
+        /// refund_period_locks : removes the locked amounts of periods of a multi period record that
+        /// are not finished, pays the provider portion of the current period and returns the
+        /// customer portion multiplied by 1000
+        ///
+        /// # Note
+        /// The current period is refunded like a single subscription and the locked amount of
+        /// next periods is given back to the customer completely
+        fn refund_period_locks(
+            &mut self,
+            provider_address: AccountId,
+            plan: &PlanConsts,
+            subscription_time: u64,
+            periods: u64,
+        ) -> u128 {
+            let time: u64 = self.env().block_timestamp();
+            let period_duration: u64 = plan.duration / periods;
+            let current_period: u64 = (time - subscription_time) / period_duration;
+            let period_price: u128 = plan.price * 1000 / u128::from(periods);
+            let mut customer_portion_locked_money: u128 = 0;
+            let mut provider_portion_locked_money: u128 = 0;
+            for period in current_period..periods {
+                let promised_amount: u128 = Self::period_promised_amount(plan, periods, period);
+                let mut customer_portion: u128 = promised_amount;
+                if period == current_period {
+                    let used: u128 = period_price
+                        * u128::from(time - subscription_time - period * period_duration)
+                        / u128::from(period_duration);
+                    customer_portion = core::cmp::min(period_price - used, promised_amount);
+                }
+                customer_portion_locked_money += customer_portion;
+                provider_portion_locked_money += promised_amount - customer_portion;
+                self.remove_entry(
+                    provider_address,
+                    (subscription_time + (period + 1) * period_duration - self.start_time) / 86400,
+                    promised_amount / 1000,
+                );
+            }
+            if provider_portion_locked_money > 0 {
+                assert_eq!(
+                    self.pay_provider(
+                        provider_address,
+                        self.providers.get(&provider_address).unwrap().money_address,
+                        provider_portion_locked_money / 1000
+                    ),
+                    Ok(())
+                );
+            }
+            customer_portion_locked_money
+        }
+
+        /// refund_record : refunds the active `SubscriptionRecord` of `caller` for `plan_index` of
+        /// `provider_address` and returns the paid amount
+        fn refund_record(
+            &mut self,
+            caller: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> u128 {
+            let time: u64 = self.env().block_timestamp();
+            assert!(
+                !self.is_disputed(caller, provider_address, plan_index),
+                "This subscription is disputed!"
+            );
+
+            let last_index =
+                match self
+                    .plan_index_to_record_index
+                    .get(&(caller, provider_address, plan_index))
+                {
+                    Some(index) => index,
+                    None => panic!("index is not valid!"),
+                };
+
+            let record_index: u128 = *last_index;
+            let record: &SubscriptionRecord = self
+                .subscription_records
+                .get(&(caller, provider_address, record_index))
+                .unwrap();
+            let max_refund_permille_policy: u128 = record.plan.max_refund_permille_policy;
+
+            // lifetime subscriptions are refunded like a subscription with refund window duration
+            let refund_duration: u64 =
+                self.refund_duration(provider_address, plan_index, &record.plan);
+            // a renewed record is not started yet, so none of it is used
+            let used_time: u64 = time.saturating_sub(record.subscription_time);
+            assert!(used_time < refund_duration);
+            let passed_time = refund_duration + record.subscription_time - self.start_time;
+
+            if record.periods > 1 {
+                let plan: PlanConsts = record.plan;
+                let subscription_time: u64 = record.subscription_time;
+                let periods: u64 = record.periods;
+                let customer_portion_locked_money: u128 =
+                    self.refund_period_locks(provider_address, &plan, subscription_time, periods);
+                assert_eq!(
+                    self.transfer(caller, customer_portion_locked_money / 1000),
+                    Ok(())
+                );
+                let add_ons_paid: u128 = self.refund_add_ons(
+                    caller,
+                    provider_address,
+                    plan_index,
+                    record_index,
+                    max_refund_permille_policy,
+                );
+                self.close_refunded_record(
+                    caller,
+                    provider_address,
+                    plan_index,
+                    record_index,
+                    customer_portion_locked_money / 1000,
+                    plan.price,
+                );
+                return customer_portion_locked_money + add_ons_paid;
+            }
+
+            let promised_amount = record.plan.price * record.plan.max_refund_permille_policy;
+            let price: u64 = (record.plan.price * 1000).try_into().unwrap();
+            let used: u64 = price * used_time / refund_duration;
+            let mut customer_portion_locked_money: u128 = (price - used).try_into().unwrap();
+
+            if customer_portion_locked_money > promised_amount {
+                // in this case the customer wants to refund very early so he want to get
+                // more than the amount of refund policy, so we can only give back just
+                // max_refund_permille_policy of his/her subscription. Whole locked money will go directly to
+                // account of the customer
+
+                customer_portion_locked_money = promised_amount;
+            } else {
+                // in this case the customer wants to refund, but he/she used most of his subscription time
+                // and now he/she will get portion of locked money, and the provider will get the rest of money
+
+                let provider_portion_locked_money =
+                    (promised_amount - customer_portion_locked_money) / 1000;
+                assert_eq!(
+                    self.pay_provider(
+                        provider_address,
+                        self.providers.get(&provider_address).unwrap().money_address,
+                        provider_portion_locked_money
+                    ),
+                    Ok(())
+                );
+            }
+            assert_eq!(
+                self.transfer(caller, customer_portion_locked_money / 1000),
+                Ok(())
+            );
+            self.remove_entry(
+                provider_address,
+                passed_time / 86400,
+                promised_amount / 1000,
+            );
+            let add_ons_paid: u128 = self.refund_add_ons(
+                caller,
+                provider_address,
+                plan_index,
+                record_index,
+                max_refund_permille_policy,
+            );
+            self.close_refunded_record(
+                caller,
+                provider_address,
+                plan_index,
+                record_index,
+                customer_portion_locked_money / 1000,
+                u128::from(price) / 1000,
+            );
+            customer_portion_locked_money + add_ons_paid
+        }
+
+        /// refund_bundle_records : refunds the active records of `caller` that are subscribed with
+        /// `bundle_index` and returns sum of the paid amounts
+        fn refund_bundle_records(&mut self, caller: AccountId, bundle_index: u128) -> u128 {
+            let plans: Vec<BundlePlan> = match self.bundles.get(&bundle_index) {
+                Some(bundle) => bundle.plans.clone(),
+                None => panic!("Bundle not existed in the contract!"),
+            };
+            let mut paid: u128 = 0;
+            for bundle_plan in plans {
+                let is_linked: bool = match self.get_active_record(
+                    caller,
+                    bundle_plan.provider,
+                    bundle_plan.plan_index,
+                ) {
+                    Some(record) => record.bundle_index == Some(bundle_index),
+                    None => false,
+                };
+                assert!(is_linked, "You are not in this bundle or already refunded");
+                paid += self.refund_record(caller, bundle_plan.provider, bundle_plan.plan_index);
+            }
+            paid
+        }
+
+        /// close_refunded_record : claws back the referral commission and marks the record of
+        /// `caller` at `record_index` as refunded, which frees its place in the plan capacity.
+        /// `refunded_amount` is the part of the paid `price` of the record that is sent back to
+        /// `caller`, which are both in the unit of the transferred balance
+        fn close_refunded_record(
+            &mut self,
+            caller: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+            record_index: u128,
+            refunded_amount: u128,
+            price: u128,
+        ) {
+            if let Some(referral) = self.referrals.take(&(caller, provider_address, plan_index)) {
+                // claw back the commission of the affiliate proportional to the refunded amount,
+                // which was paid from the provider portion so it goes back to the provider. The
+                // withdrawn part is recovered from the next commissions of the affiliate.
+                let affiliate: &mut Affiliate = self
+                    .affiliates
+                    .get_mut(&(provider_address, referral.affiliate))
+                    .unwrap();
+                // a free plan has no commission to claw back
+                let clawback: u128 = (referral.commission * refunded_amount)
+                    .checked_div(price)
+                    .unwrap_or(0);
+                let recovered: u128 = core::cmp::min(clawback, affiliate.withdrawable);
+                affiliate.earned -= clawback;
+                affiliate.withdrawable -= recovered;
+                if clawback > recovered {
+                    let debt: u128 = self.get_affiliate_debt(provider_address, referral.affiliate);
+                    self.affiliate_debts.insert(
+                        (provider_address, referral.affiliate),
+                        debt + clawback - recovered,
+                    );
+                }
+                self.pay_commission_back(provider_address, recovered);
+            }
+
+            self.subscription_records
+                .get_mut(&(caller, provider_address, record_index))
+                .unwrap()
+                .refunded = true;
+            self.clear_seats(caller, provider_address, plan_index);
+            self.remove_active_subscription(provider_address, plan_index, caller);
+            self.update_waitlist(provider_address, plan_index);
+        }
+
+        /// effective_plan : configs of a plan that `subscribe` and `renew` use now, which are the
+        /// configs of its scheduled edit if the effective time is passed
+        fn effective_plan(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Option<PlanConsts> {
+            match self.scheduled_edits.get(&(provider_address, plan_index)) {
+                Some(edit) if edit.effective_time <= self.env().block_timestamp() => {
+                    Some(edit.plan)
+                }
+                _ => self.plans.get(&(provider_address, plan_index)).copied(),
+            }
+        }
+
+        /// apply_scheduled_edit : applies the scheduled edit of a plan if its effective time is passed
+        fn apply_scheduled_edit(&mut self, provider_address: AccountId, plan_index: u128) {
+            match self.scheduled_edits.get(&(provider_address, plan_index)) {
+                Some(edit) if edit.effective_time <= self.env().block_timestamp() => {}
+                _ => return,
+            }
+            let edit: ScheduledEdit = self
+                .scheduled_edits
+                .take(&(provider_address, plan_index))
+                .unwrap();
+            self.plans.insert((provider_address, plan_index), edit.plan);
+            if !edit.grandfather {
+                self.move_subscribers_to(provider_address, plan_index, edit.plan);
+            }
+        }
+
+        /// move_subscribers_to : makes existing subscribers of a plan renew with `plan` configs,
+        /// which is the case for every edit except the grandfathering ones
+        fn move_subscribers_to(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            plan: PlanConsts,
+        ) {
+            let epoch: u32 = self.plan_epoch(provider_address, plan_index) + 1;
+            self.renewal_terms
+                .insert((provider_address, plan_index), RenewalTerms { epoch, plan });
+        }
+
+        /// plan_epoch : number of applied edits of a plan that were not grandfathering
+        fn plan_epoch(&self, provider_address: AccountId, plan_index: u128) -> u32 {
+            match self.renewal_terms.get(&(provider_address, plan_index)) {
+                Some(terms) => terms.epoch,
+                None => 0,
+            }
+        }
+
+        /// renewal_plan : configs that `record` is renewed with, which are its own `renewal_plan`
+        /// unless the plan is edited without grandfathering after the record is created
+        fn renewal_plan(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            record: &SubscriptionRecord,
+        ) -> PlanConsts {
+            match self.renewal_terms.get(&(provider_address, plan_index)) {
+                Some(terms) if terms.epoch != record.plan_epoch => terms.plan,
+                _ => record.renewal_plan,
+            }
+        }
+
+        /// set_active_subscription : counts the subscription of `user` to a plan with limited
+        /// capacity until `end_time`, instead of her previous subscription
+        fn set_active_subscription(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+            end_time: u64,
+        ) {
+            if !self
+                .plan_capacities
+                .contains_key(&(provider_address, plan_index))
+            {
+                return;
+            }
+            self.remove_active_subscription(provider_address, plan_index, user);
+            let day_id: u64 = end_time / 86400;
+            match self
+                .expiring_counts
+                .get_mut(&(provider_address, plan_index, day_id))
+            {
+                Some(expiring) => *expiring += 1,
+                None => {
+                    self.expiring_counts
+                        .insert((provider_address, plan_index, day_id), 1);
+                }
+            }
+            self.active_end_times
+                .insert((provider_address, plan_index, user), end_time);
+            self.active_counts
+                .get_mut(&(provider_address, plan_index))
+                .unwrap()
+                .count += 1;
+        }
+
+        /// remove_active_subscription : stops counting the subscription of `user` to a plan if it
+        /// is not expired
+        fn remove_active_subscription(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+        ) {
+            let end_time: u64 =
+                match self
+                    .active_end_times
+                    .take(&(provider_address, plan_index, user))
+                {
+                    Some(end_time) => end_time,
+                    None => return,
+                };
+            self.update_active_count(provider_address, plan_index);
+            let active: &mut ActiveCount = self
+                .active_counts
+                .get_mut(&(provider_address, plan_index))
+                .unwrap();
+            let day_id: u64 = end_time / 86400;
+            if day_id < active.processed_day {
+                // it is already subtracted as an expired subscription
+                return;
+            }
+            active.count -= 1;
+            *self
+                .expiring_counts
+                .get_mut(&(provider_address, plan_index, day_id))
+                .unwrap() -= 1;
+        }
+
+        /// expired_count : number of counted subscriptions of a plan that ended on at most
+        /// `MAX_EXPIRY_DAYS` days since the last update of its `ActiveCount`, and the day that the
+        /// next update starts from
+        fn expired_count(&self, provider_address: AccountId, plan_index: u128) -> (u128, u64) {
+            let active: &ActiveCount = self
+                .active_counts
+                .get(&(provider_address, plan_index))
+                .unwrap();
+            let today: u64 = self.env().block_timestamp() / 86400;
+            let last_day: u64 =
+                core::cmp::min(today, active.processed_day.saturating_add(MAX_EXPIRY_DAYS));
+            let mut expired: u128 = 0;
+            let mut day_id: u64 = active.processed_day;
+            while day_id < last_day && expired < active.count {
+                if let Some(expiring) =
+                    self.expiring_counts
+                        .get(&(provider_address, plan_index, day_id))
+                {
+                    expired += *expiring;
+                }
+                day_id += 1;
+            }
+            if expired == active.count {
+                // no counted subscription is left, so none of the next days has an expiry
+                return (expired, today);
+            }
+            (expired, last_day)
+        }
+
+        /// update_active_count : subtracts the expired subscriptions from `ActiveCount` of a plan
+        fn update_active_count(&mut self, provider_address: AccountId, plan_index: u128) {
+            if !self
+                .active_counts
+                .contains_key(&(provider_address, plan_index))
+            {
+                return;
+            }
+            let (expired, processed_day) = self.expired_count(provider_address, plan_index);
+            let active: &mut ActiveCount = self
+                .active_counts
+                .get_mut(&(provider_address, plan_index))
+                .unwrap();
+            active.count -= expired;
+            active.processed_day = processed_day;
+        }
+
+        /// update_waitlist : removes expired subscriptions and offers of a plan with limited
+        /// capacity and offers the free places to the first waitlisted users
+        fn update_waitlist(&mut self, provider_address: AccountId, plan_index: u128) {
+            let capacity: PlanCapacity =
+                match self.plan_capacities.get(&(provider_address, plan_index)) {
+                    Some(capacity) => *capacity,
+                    None => return,
+                };
+            let time: u64 = self.env().block_timestamp();
+            self.update_active_count(provider_address, plan_index);
+            let active_count: u128 = self.get_active_count(provider_address, plan_index);
+            let mut offers: Vec<WaitlistOffer> = self
+                .waitlist_offers
+                .take(&(provider_address, plan_index))
+                .unwrap_or_default();
+            offers.retain(|x| x.deadline >= time);
+            let mut reserved: u128 = offers.len().try_into().unwrap();
+            let mut waitlist: Vec<AccountId> = self
+                .waitlists
+                .take(&(provider_address, plan_index))
+                .unwrap_or_default();
+            while active_count + reserved < capacity.max_active && !waitlist.is_empty() {
+                let user: AccountId = waitlist.remove(0);
+                let deadline: u64 = time + capacity.claim_window;
+                offers.push(WaitlistOffer { user, deadline });
+                reserved += 1;
+                self.env().emit_event(WaitlistOfferEvent {
+                    provider: provider_address,
+                    user_address: user,
+                    plan_index,
+                    deadline,
+                });
+            }
+            if !waitlist.is_empty() {
+                self.waitlists
+                    .insert((provider_address, plan_index), waitlist);
+            }
+            self.waitlist_offers
+                .insert((provider_address, plan_index), offers);
+        }
+
+        /// has_free_place : checks that a place of a plan with limited capacity is offered to
+        /// `user` or is not reserved by any active subscription or offer
+        fn has_free_place(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+            capacity: &PlanCapacity,
+        ) -> bool {
+            let offers: &Vec<WaitlistOffer> = self
+                .waitlist_offers
+                .get(&(provider_address, plan_index))
+                .unwrap();
+            if offers.iter().any(|x| x.user == user) {
+                return true;
+            }
+            let reserved: u128 = offers.len().try_into().unwrap();
+            self.get_active_count(provider_address, plan_index) + reserved < capacity.max_active
+        }
+
+        /// take_capacity : reserves a place of a plan for `user` and takes her offer if she has one
+        fn take_capacity(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+        ) {
+            let capacity: PlanCapacity =
+                match self.plan_capacities.get(&(provider_address, plan_index)) {
+                    Some(capacity) => *capacity,
+                    None => return,
+                };
+            self.update_waitlist(provider_address, plan_index);
+            assert!(
+                self.has_free_place(provider_address, plan_index, user, &capacity),
+                "Plan is full!"
+            );
+            self.waitlist_offers
+                .get_mut(&(provider_address, plan_index))
+                .unwrap()
+                .retain(|x| x.user != user);
+        }
+
+        /// is_allowed_to_subscribe : checks allowlist of a plan if it is `Visibility::InviteOnly`
+        fn is_allowed_to_subscribe(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            user: AccountId,
+        ) -> bool {
+            match self.plan_metadata.get(&(provider_address, plan_index)) {
+                Some(metadata) if metadata.visibility == Visibility::InviteOnly => {
+                    self.is_allowlisted(provider_address, plan_index, user)
+                }
+                _ => true,
+            }
+        }
+
+        /// schema_version : current version of `CharacteristicSchema` of a plan, 0 if it is never changed
+        fn schema_version(&self, provider_address: AccountId, plan_index: u128) -> u32 {
+            match self
+                .characteristic_schemas
+                .get(&(provider_address, plan_index))
+            {
+                Some(schema) => schema.version,
+                None => 0,
+            }
+        }
+
+        /// edit_schema : increases the version of `CharacteristicSchema` of a plan and returns it
+        fn edit_schema(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> &mut CharacteristicSchema {
+            if !self
+                .characteristic_schemas
+                .contains_key(&(provider_address, plan_index))
+            {
+                self.characteristic_schemas.insert(
+                    (provider_address, plan_index),
+                    CharacteristicSchema::default(),
+                );
+            }
+            let schema: &mut CharacteristicSchema = self
+                .characteristic_schemas
+                .get_mut(&(provider_address, plan_index))
+                .unwrap();
+            schema.version += 1;
+            schema
+        }
+
+        /// validate_characteristics : checks `values` against `CharacteristicSchema` of a plan
+        fn validate_characteristics(
+            &self,
+            provider_address: AccountId,
+            plan_index: u128,
+            values: &[String],
+        ) {
+            let schema: &CharacteristicSchema = match self
+                .characteristic_schemas
+                .get(&(provider_address, plan_index))
+            {
+                Some(schema) => schema,
+                None => return,
+            };
+            // values are only readable if they are not encrypted
+            let is_plain: bool = !self.encryption_keys.contains_key(&provider_address);
+            for (spec, value) in schema.specs.iter().zip(values.iter()) {
+                if value.is_empty() {
+                    assert!(!spec.required, "invalid characteristic values!");
+                    continue;
+                }
+                assert!(
+                    spec.max_size == 0 || value.len() <= spec.max_size as usize,
+                    "invalid characteristic values!"
+                );
+                match &spec.kind {
+                    CharacteristicType::Enum(options) => match value.parse::<usize>() {
+                        Ok(option) => {
+                            assert!(option < options.len(), "invalid characteristic values!")
+                        }
+                        Err(_) => panic!("invalid characteristic values!"),
+                    },
+                    CharacteristicType::Email if is_plain => {
+                        assert!(Self::is_email(value), "invalid characteristic values!")
+                    }
+                    CharacteristicType::Number if is_plain => {
+                        assert!(Self::is_number(value), "invalid characteristic values!")
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        /// is_email : checks that `value` has a non-empty local part and a dotted domain
+        fn is_email(value: &str) -> bool {
+            let mut parts = value.split('@');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(local), Some(domain), None) => {
+                    !local.is_empty()
+                        && domain.contains('.')
+                        && !domain.starts_with('.')
+                        && !domain.ends_with('.')
+                        && !value.contains(char::is_whitespace)
+                }
+                _ => false,
+            }
+        }
+
+        /// is_number : checks that `value` is a decimal number with an optional sign and fraction
+        fn is_number(value: &str) -> bool {
+            let digits: &str = value.strip_prefix('-').unwrap_or(value);
+            let mut parts = digits.split('.');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(integer), fraction, None) => {
+                    !integer.is_empty()
+                        && integer.bytes().all(|x| x.is_ascii_digit())
+                        && match fraction {
+                            Some(fraction) => {
+                                !fraction.is_empty() && fraction.bytes().all(|x| x.is_ascii_digit())
+                            }
+                            None => true,
+                        }
+                }
+                _ => false,
+            }
+        }
+
+        /// username_address : address of `username` which is looked up by its exact form and then
+        /// by its case folded form, so usernames that are stored before case folding are found
+        /// even if their folded form is taken by another account
+        fn username_address(&self, username: &str) -> Option<AccountId> {
+            match self.username_to_address.get(&username.to_string()) {
+                Some(address) => Some(*address),
+                None => self
+                    .username_to_address
+                    .get(&Self::normalize_username(username))
+                    .copied(),
+            }
+        }
+
+        /// normalize_username : case folding of `username` which is applied before any lookup
+        fn normalize_username(username: &str) -> String {
+            username.to_ascii_lowercase()
+        }
+
+        /// username_status : checks that normalized `username` is valid, not reserved, not taken and
+        /// its release cooldown is passed
+        fn username_status(&self, username: &String) -> UsernameStatus {
+            let length: u32 = username.len().try_into().unwrap_or(u32::MAX);
+            if length < self.configs.username_min_length {
+                return UsernameStatus::TooShort;
+            }
+            if length > self.configs.username_max_length {
+                return UsernameStatus::TooLong;
+            }
+            if !username
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-' || c == b'.')
+            {
+                return UsernameStatus::InvalidCharacter;
+            }
+            if self.reserved_usernames.contains_key(username) {
+                return UsernameStatus::Reserved;
+            }
+            if self.username_to_address.contains_key(username) {
+                return UsernameStatus::Taken;
+            }
+            match self.released_usernames.get(username) {
+                Some(claimable_time) if *claimable_time > self.env().block_timestamp() => {
+                    UsernameStatus::Cooldown
+                }
+                _ => UsernameStatus::Available,
+            }
+        }
+
+        /// release_username_of : removes username of `user` from both username maps and starts
+        /// its release cooldown
+        fn release_username_of(&mut self, user: AccountId) {
+            let username: String = self.address_to_username.take(&user).unwrap();
+            self.username_to_address.take(&username);
+            self.username_transfers.take(&user);
+            self.released_usernames.insert(
+                username,
+                self.env().block_timestamp() + self.configs.username_release_cooldown,
+            );
+        }
+
+        /// subscribe_plan : subscribes `caller` to `periods` durations of `plan_index` of
+        /// `provider_address`, it is used by `subscribe` and `subscribe_periods`
+        fn subscribe_plan(
+            &mut self,
+            provider_address: AccountId,
+            plan_index: u128,
+            pass: [u8; 32],
+            username: String,
+            characteristics_values_encrypted: Vec<String>,
+            options: SubscribeOptions,
+        ) {
+            let SubscribeOptions { referrer, periods } = options;
+            let caller: AccountId = self.env().caller();
+            let username: String = Self::normalize_username(&username);
+            let time: u64 = self.env().block_timestamp();
+            self.apply_scheduled_edit(provider_address, plan_index);
+
+            assert!(
+                !self.check_subscription(caller, provider_address, plan_index),
+                "You are already subscribed to this plan!"
+            );
+
+            let provider = match self.providers.get(&provider_address) {
+                Some(provider) => provider,
+                None => panic!("Provider not existed in the contract!"),
+            };
+
+            assert!(provider.plan_count > plan_index, "Wrong plan index!");
+
+            let consts: PlanConsts = *self.plans.get(&(provider_address, plan_index)).unwrap();
+            let plan_characteristics: Vec<String> = self
+                .plans_characteristics
+                .get(&(provider_address, plan_index))
+                .unwrap()
+                .clone();
+
+            assert_eq!(
+                characteristics_values_encrypted.len(),
+                plan_characteristics.len(),
+                "invalid characteristic values!"
+            );
+            assert_eq!(
+                self.periods_price(provider_address, plan_index, consts.price, periods),
+                self.env().transferred_balance(),
+                "You have to pay exact plan price"
+            );
+            assert!(!consts.disabled, "Plan is currently disabled by provider");
+            assert!(
+                self.is_allowed_to_subscribe(provider_address, plan_index, caller),
+                "You are not in the allowlist of this plan!"
+            );
+            let plan_characteristics: Vec<String> = self
+                .plans_characteristics
+                .get(&(provider_address, plan_index))
+                .unwrap()
+                .clone();
+
+            assert_eq!(
+                characteristics_values_encrypted.len(),
+                plan_characteristics.len(),
+                "invalid characteristic values!"
+            );
+            self.validate_characteristics(
+                provider_address,
+                plan_index,
+                &characteristics_values_encrypted,
+            );
+
+            let mut address_has_not_username: bool = true;
+            if self.address_to_username.contains_key(&caller) {
+                address_has_not_username = false;
+            } else {
+                assert_eq!(
+                    self.username_status(&username),
+                    UsernameStatus::Available,
+                    "username is invalid!"
+                );
+            }
+
+            let money_address: AccountId = provider.money_address;
+            self.take_capacity(provider_address, plan_index, caller);
+            assert!(
+                periods == 1 || consts.kind != PlanKind::Lifetime,
+                "Lifetime plans can not be subscribed for multiple periods!"
+            );
+            // a multi period subscription is stored as one record which covers all periods
+            let lock_duration: u64 = self.refund_duration(provider_address, plan_index, &consts);
+            let renewal_plan: PlanConsts = consts;
+            // overflow checks are off in release builds, so the total duration is checked here
+            let duration: u64 = match consts.duration.checked_mul(periods) {
+                Some(duration) if periods == 1 || time.checked_add(duration).is_some() => duration,
+                _ => panic!("Wrong number of periods!"),
+            };
+            let consts: PlanConsts = PlanConsts {
+                duration,
+                price: self.env().transferred_balance(),
+                ..consts
+            };
+            let provider_portion: u128 =
+                consts.price * (1000 - consts.max_refund_permille_policy) / 1000;
+            let commission: u128 = match referrer {
+                Some(affiliate) => {
+                    assert!(affiliate != caller, "You can not refer yourself!");
+                    match self.affiliates.get(&(provider_address, affiliate)) {
+                        Some(x) => provider_portion * x.commission_permille / 1000,
+                        None => panic!("Referrer is not an affiliate of this provider!"),
+                    }
+                }
+                None => 0,
+            };
+            // send money to money_address (1000 - plan.max_refund_permille_policy) / 1000;
+            assert_eq!(
+                self.pay_provider(
+                    provider_address,
+                    money_address,
+                    provider_portion - commission
+                ),
+                Ok(())
+            );
+
+            if address_has_not_username {
+                self.address_to_username.insert(caller, username.clone());
+                self.username_to_address.insert(username, caller);
+            }
+
+            let characteristics_values: Vec<String> = characteristics_values_encrypted.clone();
+
+            let subscription_record = SubscriptionRecord {
+                provider: provider_address,
+                plan: consts,
+                plan_index,
+                subscription_time: time,
+                characteristics_values_encrypted,
+                key_version: self.encryption_key_version(provider_address),
+                characteristics_version: 0,
+                schema_version: self.schema_version(provider_address, plan_index),
+                periods,
+                renewal_plan,
+                plan_epoch: self.plan_epoch(provider_address, plan_index),
+                bundle_index: None,
+                complimentary: false,
+                refunded: false,
+            };
+            self.push_record(caller, pass, subscription_record);
+            // refundable part of each period is locked until the end of that period
+            for period in 0..periods {
+                self.add_entry(
+                    provider_address,
+                    (time + (period + 1) * lock_duration - self.start_time) / 86400,
+                    Self::period_promised_amount(&consts, periods, period) / 1000,
+                );
+            }
+            self.set_active_subscription(
+                provider_address,
+                plan_index,
+                caller,
+                time.saturating_add(consts.duration),
+            );
+            self.add_plan_stats(provider_address, plan_index, false, consts.price);
+
+            match referrer {
+                Some(affiliate) => {
+                    // claw backs of withdrawn commissions are recovered first
+                    let debt: u128 = self.get_affiliate_debt(provider_address, affiliate);
+                    let recovered: u128 = core::cmp::min(debt, commission);
+                    if debt > 0 {
+                        self.affiliate_debts
+                            .insert((provider_address, affiliate), debt - recovered);
+                    }
+                    self.pay_commission_back(provider_address, recovered);
+                    let x = self
+                        .affiliates
+                        .get_mut(&(provider_address, affiliate))
+                        .unwrap();
+                    x.earned += commission;
+                    x.withdrawable += commission - recovered;
+                    self.referrals.insert(
+                        (caller, provider_address, plan_index),
+                        Referral {
+                            affiliate,
+                            commission,
+                        },
+                    );
+                    self.env().emit_event(ReferralEvent {
+                        provider: provider_address,
+                        affiliate,
+                        user_address: caller,
+                        commission,
+                    });
+                }
+                None => {
+                    self.referrals.take(&(caller, provider_address, plan_index));
+                }
+            }
+
+            
+            self.env().emit_event(SubscribeEvent {
+                provider: provider_address,
+                plan_index,
+                user_address: caller,
+                subscription_time: time,
+                duration: consts.duration,
+                price: consts.price,
+                characteristics: characteristics_values
+            });
+        }
+
+        /// push_record : adds `subscription_record` as the last record of `user` for its plan
+        fn push_record(
+            &mut self,
+            user: AccountId,
+            pass: [u8; 32],
+            subscription_record: SubscriptionRecord,
+        ) {
+            let provider_address: AccountId = subscription_record.provider;
+            let plan_index: u128 = subscription_record.plan_index;
+            // the disputed record has to stay the last record until its dispute is closed
+            assert!(
+                !self.is_disputed(user, provider_address, plan_index),
+                "This subscription is disputed!"
             );
-            self.retrieve_data(user, provider_address)
-        }
+            // seats of the previous team subscription are not carried over to a new subscription
+            self.clear_seats(user, provider_address, plan_index);
+            if !self.users.contains_key(&user) {
+                self.users.insert(
+                    user,
+                    User {
+                        list_of_providers: Vec::new(),
+                        subscrypt_pass_hash: pass,
+                    },
+                );
+            }
 
-        /// `user` can use this function to retrieve her subscriptions history for a specific
-        /// provider with her wallet.
-        ///
-        /// # Returns
-        /// `Vec<SubscriptionRecord>` is returned which is a vector of `SubscriptionRecord` struct
-        ///
-        /// # Example
-        /// Examples in `retrieve_data_with_password_works` in `tests/test.rs`
-        #[ink(message)]
-        pub fn retrieve_data_with_wallet(
-            &self,
-            provider_address: AccountId,
-        ) -> Vec<SubscriptionRecord> {
-            let caller: AccountId = self.env().caller();
-            self.retrieve_data(caller, provider_address)
-        }
+            if let Some(plan_record) = self.records.get_mut(&(user, provider_address)) {
+                let record_index: u128 = plan_record.record_count;
+                plan_record.record_count += 1;
+                self.plan_index_to_record_index
+                    .insert((user, provider_address, plan_index), record_index);
 
-        /// We can get plan data in this function
-        ///
-        /// # Returns
-        /// `PlanConsts` is returned
-        ///
-        /// # Example
-        /// Examples in `tests/test.rs`
-        #[ink(message)]
-        pub fn get_plan_data(&self, provider_address: AccountId, plan_index: u128) -> PlanConsts {
-            let number: usize = plan_index.try_into().unwrap();
-            match self.providers.get(&provider_address) {
-                Some(provider) => match provider.plans.get(number) {
-                    Some(x) => *x,
-                    None => panic!("please select a valid plan"),
-                },
-                None => panic!("provider address is not valid!"),
+                self.subscription_records
+                    .insert((user, provider_address, record_index), subscription_record);
+            } else {
+                self.users
+                    .get_mut(&user)
+                    .unwrap()
+                    .list_of_providers
+                    .push(provider_address);
+
+                let plan_record: PlanRecord = PlanRecord {
+                    record_count: 1,
+                    pass_hash: pass,
+                };
+
+                self.records.insert((user, provider_address), plan_record);
+
+                self.plan_index_to_record_index
+                    .insert((user, provider_address, plan_index), 0);
+                self.subscription_records
+                    .insert((user, provider_address, 0), subscription_record);
             }
         }
 
-        /// get user's plan characteristics in this function
-        ///
-        /// # Returns
-        /// `user's plan characteristics` is returned
-        ///
-        /// # Example
-        /// Examples in `tests/test.rs` in get_user_plan_characteristics_works test
-        #[ink(message)]
-        pub fn get_user_plan_characteristics(&self, user: AccountId, provider_address: AccountId, plan_index: u128) -> Vec<String>  { // TODO: should add authentication
-            if !self
-                .plan_index_to_record_index
-                .contains_key(&(user, provider_address, plan_index))
-            {
-                panic!("user doesn't have this plan!");
-            }
-            let last_index: u128 = *self
-                .plan_index_to_record_index
-                .get(&(user, provider_address, plan_index))
+        /// get_active_record : returns last `SubscriptionRecord` of `user` for `plan_index` of
+        /// `provider_address` if it is not refunded or expired
+        fn get_active_record(
+            &self,
+            user: AccountId,
+            provider_address: AccountId,
+            plan_index: u128,
+        ) -> Option<&SubscriptionRecord> {
+            let last_index: u128 =
+                *self
+                    .plan_index_to_record_index
+                    .get(&(user, provider_address, plan_index))?;
+            let record: &SubscriptionRecord = self
+                .subscription_records
+                .get(&(user, provider_address, last_index))
                 .unwrap();
-            let number: usize = last_index.try_into().unwrap();
-            let record: &SubscriptionRecord = &self
-                .records
-                .get(&(user, provider_address))
-                .unwrap()
-                .subscription_records[number];
             if record.plan_index != plan_index
                 || record.refunded
-                || record.plan.duration + record.subscription_time < self.env().block_timestamp()
+                || record
+                    .subscription_time
+                    .saturating_add(record.plan.duration)
+                    < self.env().block_timestamp()
             {
-                panic!("user doesn't have this plan!");
-            }
-            record.characteristics_values_encrypted.clone()
-        }
-
-        /// We can get plan count in this function
-        ///
-        /// # Returns
-        /// a number is returned
-        ///
-        /// # Example
-        /// Examples in `tests/test.rs`
-        #[ink(message)]
-        pub fn get_plan_length(&self, provider_address: AccountId) -> u128 {
-            match self.providers.get(&provider_address) {
-                Some(provider) => provider.plans.len().try_into().unwrap(),
-                None => 0,
+                return None;
             }
+            Some(record)
         }
 
-        /// We can get plan characteristic keys in this function
-        ///
-        /// # Returns
-        /// `Vec<String>` is returned
-        ///
-        /// # Example
-        /// Examples in `tests/test.rs`
-        #[ink(message)]
-        pub fn get_plan_characteristics(
+        /// seat_count : number of seats of the team subscription of `admin`, including her own seat
+        fn seat_count(
             &self,
+            admin: AccountId,
             provider_address: AccountId,
             plan_index: u128,
-        ) -> Vec<String> {
-            let number: usize = plan_index.try_into().unwrap();
-            match match self.providers.get(&provider_address) {
-                Some(provider) => provider,
-                None => panic!("index is not valid!"),
-            }
-            .plans_characteristics
-            .get(number)
-            {
-                Some(x) => x.clone(),
-                None => panic!("please select a valid plan"),
+        ) -> u128 {
+            match self.seats.get(&(admin, provider_address, plan_index)) {
+                Some(members) => 1 + members.len() as u128,
+                None => 1,
             }
         }
 
-        /// Get hash of String
-        ///
-        /// # Returns
-        /// `String` is returned
-        ///    
-        #[ink(message)]
-        pub fn get_sha2(&self, string: String) -> [u8; 32] {
-            let mut output = <Sha2x256 as HashOutput>::Type::default(); // 256-bit buffer
-            ink_env::hash_encoded::<Sha2x256, _>(&string, &mut output);
-            return output;
+        /// clear_seats : revokes every seat of the team subscription of `admin` when her subscription
+        /// is closed or replaced by a new subscription
+        fn clear_seats(&mut self, admin: AccountId, provider_address: AccountId, plan_index: u128) {
+            if let Some(members) = self.seats.take(&(admin, provider_address, plan_index)) {
+                for member in members {
+                    self.seat_admins
+                        .take(&(member, provider_address, plan_index));
+                    self.env().emit_event(SeatRevokedEvent {
+                        provider: provider_address,
+                        plan_index,
+                        admin,
+                        member,
+                    });
+                }
+            }
         }
 
-        /// This function can be called to check if `user` has a valid subscription to the
-        /// specific `plan_index` of `provider`.
-        ///
-        /// # Note
-        /// if `user` refunded or her subscription is expired then this function will return false
-        ///
-        /// # Returns
-        /// `bool` which means if `user` is subscribed or not
-        ///
-        /// # Example
-        /// Examples in `check_subscription_works` in `tests/test.rs`
-        #[ink(message)]
-        pub fn check_subscription(
-            &self,
+        /// usage_report_hash : Blake2x256 hash of the usage report that the meter key signs
+        fn usage_report_hash(
             user: AccountId,
             provider_address: AccountId,
             plan_index: u128,
-        ) -> bool {
-            if !self
-                .plan_index_to_record_index
-                .contains_key(&(user, provider_address, plan_index))
-            {
-                return false;
-            }
-            let last_index: u128 = *self
-                .plan_index_to_record_index
-                .get(&(user, provider_address, plan_index))
-                .unwrap();
-            let number: usize = last_index.try_into().unwrap();
-            let record: &SubscriptionRecord = &self
-                .records
-                .get(&(user, provider_address))
-                .unwrap()
-                .subscription_records[number];
-            if record.plan_index != plan_index
-                || record.refunded
-                || record.plan.duration + record.subscription_time < self.env().block_timestamp()
-            {
-                return false;
-            }
-            true
+            units: u128,
+            nonce: u64,
+        ) -> [u8; 32] {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink_env::hash_encoded::<Blake2x256, _>(
+                &(user, provider_address, plan_index, units, nonce),
+                &mut output,
+            );
+            output
         }
 
-        /// This function can be called to check if `user` has a valid subscription to the
-        /// specific `plan_index` of `provider`.
-        ///
-        /// # Note
-        /// if `user` refunded or her subscription is expired then this function will return false
-        ///
-        /// # Returns
-        /// `bool` which means if `user` is subscribed or not
-        ///
-        /// # Example
-        /// Examples in `check_subscription_works` in `tests/test.rs`
-        #[ink(message)]
-        pub fn check_subscription_with_username(
-            &self,
-            username: String,
-            provider_address: AccountId,
-            plan_index: u128,
-        ) -> bool {
-            match self.username_to_address.get(&username) {
-                Some(name) => self.check_subscription(*name, provider_address, plan_index),
-                None => false,
+        /// prorated_price : `plan.price` prorated to the remaining time until `end_time`
+        fn prorated_price(plan: &PlanConsts, end_time: u64, time: u64) -> u128 {
+            if plan.duration == 0 {
+                return plan.price;
             }
+            plan.price * u128::from(end_time.saturating_sub(time)) / u128::from(plan.duration)
         }
 
         fn retrieve_whole_data(&self, caller: AccountId) -> Vec<SubscriptionRecord> {
@@ -1430,17 +7175,28 @@ pub mod subscrypt {
             let mut data: Vec<SubscriptionRecord> = Vec::new();
 
             let plan_records: &PlanRecord = self.records.get(&(caller, provider_address)).unwrap();
-            for i in 0..plan_records.subscription_records.len() {
+            for i in 0..plan_records.record_count {
+                let record: &SubscriptionRecord = self
+                    .subscription_records
+                    .get(&(caller, provider_address, i))
+                    .unwrap();
                 let k = SubscriptionRecord {
-                    provider: plan_records.subscription_records[i].provider,
-                    plan: plan_records.subscription_records[i].plan,
-                    plan_index: plan_records.subscription_records[i].plan_index,
-                    subscription_time: plan_records.subscription_records[i].subscription_time,
-                    characteristics_values_encrypted: plan_records.subscription_records[i]
+                    provider: record.provider,
+                    plan: record.plan,
+                    plan_index: record.plan_index,
+                    subscription_time: record.subscription_time,
+                    characteristics_values_encrypted: record
                         .characteristics_values_encrypted
                         .clone(),
-
-                    refunded: plan_records.subscription_records[i].refunded,
+                    key_version: record.key_version,
+                    characteristics_version: record.characteristics_version,
+                    schema_version: record.schema_version,
+                    periods: record.periods,
+                    renewal_plan: record.renewal_plan,
+                    plan_epoch: record.plan_epoch,
+                    bundle_index: record.bundle_index,
+                    complimentary: record.complimentary,
+                    refunded: record.refunded,
                 };
                 data.push(k);
             }
@@ -1454,6 +7210,63 @@ pub mod subscrypt {
             })
         }
 
+        /// pay_provider : transfer `amount` to provider after deducting the platform commission
+        /// which is kept in `platform_balance`
+        /// # arguments:
+        /// * provider_address
+        /// * receiver : the account that is paid if `provider_address` has not set any `Payee`
+        /// * amount : money amount
+        ///
+        /// # Note
+        /// if `provider_address` has set payees, the amount is split by their `share_permille` and
+        /// the remaining dust of rounding is paid to the first payee
+        fn pay_provider(
+            &mut self,
+            provider_address: AccountId,
+            receiver: AccountId,
+            amount: u128,
+        ) -> Result<(), Error> {
+            let fee: u128 = amount * self.configs.platform_fee_basis_points / 10000;
+            *self.platform_balance += fee;
+            self.pay_payees(provider_address, receiver, amount - fee)
+        }
+
+        /// pay_payees : splits `net_amount` between payees of `provider_address`, or sends it to
+        /// `receiver` if provider has no payees
+        fn pay_payees(
+            &mut self,
+            provider_address: AccountId,
+            receiver: AccountId,
+            net_amount: u128,
+        ) -> Result<(), Error> {
+            match self.payees.get(&provider_address) {
+                Some(payees) => {
+                    let mut paid: u128 = 0;
+                    for payee in payees.iter().skip(1) {
+                        let share: u128 = net_amount * payee.share_permille / 1000;
+                        self.transfer(payee.address, share)?;
+                        paid += share;
+                    }
+                    self.transfer(payees[0].address, net_amount - paid)
+                }
+                None => self.transfer(receiver, net_amount),
+            }
+        }
+
+        /// pay_commission_back : pays clawed back `amount` of affiliate commissions to provider
+        /// without platform fee, because the commissions did not pay it
+        fn pay_commission_back(&mut self, provider_address: AccountId, amount: u128) {
+            if amount == 0 {
+                return;
+            }
+            let money_address: AccountId =
+                self.providers.get(&provider_address).unwrap().money_address;
+            assert_eq!(
+                self.pay_payees(provider_address, money_address, amount),
+                Ok(())
+            );
+        }
+
         /// add_entry : add a payment entry to provider payment management linked list
         /// # arguments:
         /// * provider_address
@@ -1548,6 +7361,10 @@ pub mod subscrypt {
         /// * day_id : the calculation formula is : (finish date - contract start date) / 86400
         /// * amount
         fn remove_entry(&mut self, provider_address: AccountId, day_id: u64, amount: u128) {
+            if amount == 0 {
+                // records that are not paid in the contract have no entry
+                return;
+            }
             self.daily_locked_amounts
                 .get_mut(&(provider_address, day_id))
                 .unwrap()
@@ -1602,6 +7419,77 @@ pub mod subscrypt {
         }
     }
 
+    impl<K, V> Mapping<K, V>
+    where
+        K: Clone + Ord + PackedLayout,
+        V: PackedLayout,
+    {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn get(&self, key: &K) -> Option<&V> {
+            self.values.get(key)
+        }
+
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            self.values.get_mut(key)
+        }
+
+        pub fn contains_key(&self, key: &K) -> bool {
+            self.values.get(key).is_some()
+        }
+
+        /// insert : inserts `value` for `key` and returns its previous value
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            self.values.put_get(&key, Some(value))
+        }
+
+        /// take : removes the value of `key` and returns it
+        pub fn take(&mut self, key: &K) -> Option<V> {
+            self.values.put_get(key, None)
+        }
+    }
+
+    impl<K, V> Default for Mapping<K, V>
+    where
+        K: Ord + scale::Encode,
+        V: PackedLayout,
+    {
+        fn default() -> Self {
+            Self {
+                values: LazyHashMap::new(),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<K, V> StorageLayout for Mapping<K, V>
+    where
+        K: Ord + scale::Encode,
+        V: PackedLayout + scale_info::TypeInfo + 'static,
+    {
+        fn layout(key_ptr: &mut KeyPtr) -> Layout {
+            <LazyHashMap<K, V, Blake2x256> as StorageLayout>::layout(key_ptr)
+        }
+    }
+
+    impl Configs {
+        /// new : configs of a new contract that is managed by `owner`
+        pub fn new(owner: AccountId) -> Self {
+            Self {
+                owner,
+                platform_fee_basis_points: 0,
+                username_release_cooldown: 60 * 60 * 24 * 30,
+                username_min_length: 3,
+                username_max_length: 32,
+                arbiter: owner,
+                dispute_response_window: 60 * 60 * 24 * 7,
+                dispute_arbitration_window: 60 * 60 * 24 * 14,
+            }
+        }
+    }
+
     impl LinkedList {
         pub fn new() -> Self {
             LinkedList::default()
@@ -1615,3 +7503,146 @@ pub mod subscrypt {
         }
     }
 }
+
+/// Host functions of `pallet-contracts` that are not exposed by `ink_env`
+///
+/// # Note
+/// A Wasm module that imports a host function which is missing on the chain can not be
+/// instantiated, so the chain has to provide all of these functions.
+#[cfg(not(feature = "std"))]
+mod seal {
+    #[link(wasm_import_module = "seal0")]
+    extern "C" {
+        fn seal_set_code_hash(code_hash_ptr: *const u8) -> u32;
+        fn seal_ecdsa_recover(
+            signature_ptr: *const u8,
+            message_hash_ptr: *const u8,
+            output_ptr: *mut u8,
+        ) -> u32;
+    }
+
+    /// set_code_hash : replaces the code of the contract, returns the error code of the host
+    /// function which is 0 on success
+    pub fn set_code_hash(code_hash: &[u8]) -> u32 {
+        // SAFETY: the host only reads the 32 bytes of a code hash from the pointer, `code_hash`
+        // is the bytes of a `Hash` so it has exactly 32 bytes and stays borrowed during the call
+        unsafe { seal_set_code_hash(code_hash.as_ptr()) }
+    }
+
+    /// ecdsa_recover : writes the compressed public key that signed `message_hash` to `output`,
+    /// returns the error code of the host function which is 0 on success
+    pub fn ecdsa_recover(
+        signature: &[u8; 65],
+        message_hash: &[u8; 32],
+        output: &mut [u8; 33],
+    ) -> u32 {
+        // SAFETY: the host reads 65 bytes of signature and 32 bytes of message hash and writes
+        // 33 bytes of public key, which are exactly the sizes of the borrowed arrays
+        unsafe {
+            seal_ecdsa_recover(
+                signature.as_ptr(),
+                message_hash.as_ptr(),
+                output.as_mut_ptr(),
+            )
+        }
+    }
+}
+
+/// Recovery of secp256k1 ECDSA signatures, which is done by the chain on-chain and by the
+/// `secp256k1` crate off-chain
+mod ecdsa {
+    /// recover : compressed public key that signed `message_hash`, the last byte of `signature`
+    /// is the recovery id
+    #[cfg(not(feature = "std"))]
+    pub fn recover(signature: &[u8; 65], message_hash: &[u8; 32]) -> Option<[u8; 33]> {
+        let mut output = [0; 33];
+        match super::seal::ecdsa_recover(signature, message_hash, &mut output) {
+            0 => Some(output),
+            _ => None,
+        }
+    }
+
+    /// recover : compressed public key that signed `message_hash`, the last byte of `signature`
+    /// is the recovery id
+    #[cfg(feature = "std")]
+    pub fn recover(signature: &[u8; 65], message_hash: &[u8; 32]) -> Option<[u8; 33]> {
+        use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+        use secp256k1::{Message, Secp256k1};
+
+        let recovery_id = RecoveryId::from_i32(i32::from(signature[64])).ok()?;
+        let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id).ok()?;
+        let message = Message::from_slice(message_hash).ok()?;
+        let public_key = Secp256k1::verification_only()
+            .recover_ecdsa(&message, &signature)
+            .ok()?;
+        Some(public_key.serialize())
+    }
+}
+
+/// Off-chain helpers for providers that import their subscriptions by `import_subscriptions`
+#[cfg(feature = "std")]
+pub mod import {
+    use super::subscrypt::{ImportedSubscription, MAX_IMPORT_BATCH_SIZE};
+    use ink_env::AccountId;
+
+    /// Parses a CSV file of subscriptions and splits them into chunks of at most `batch_size`
+    /// (capped by `MAX_IMPORT_BATCH_SIZE`), each chunk is the argument of one
+    /// `import_subscriptions` call
+    ///
+    /// Each line is `user,plan_index,start_time,end_time` where `user` is the hex encoded
+    /// account id, an optional header line and empty lines are skipped.
+    ///
+    /// # Returns
+    /// the chunks, or an error if `batch_size` is 0 or a line is invalid
+    ///
+    /// # Examples
+    /// Examples in `chunk_import_csv_works` in `tests/test.rs`
+    pub fn chunk_import_csv(
+        csv: &str,
+        batch_size: usize,
+    ) -> Result<Vec<Vec<ImportedSubscription>>, String> {
+        if batch_size == 0 {
+            return Err("batch_size can not be 0".to_string());
+        }
+        let batch_size: usize = core::cmp::min(batch_size, MAX_IMPORT_BATCH_SIZE);
+        let mut subscriptions: Vec<ImportedSubscription> = Vec::new();
+        for (number, line) in csv.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || (number == 0 && line.starts_with("user")) {
+                continue;
+            }
+            subscriptions
+                .push(parse_line(line).map_err(|err| format!("line {}: {}", number + 1, err))?);
+        }
+        Ok(subscriptions
+            .chunks(batch_size)
+            .map(|chunk| chunk.to_vec())
+            .collect())
+    }
+
+    fn parse_line(line: &str) -> Result<ImportedSubscription, String> {
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        if fields.len() != 4 {
+            return Err("expected 4 fields".to_string());
+        }
+        Ok(ImportedSubscription {
+            user: parse_account(fields[0])?,
+            plan_index: fields[1].parse().map_err(|_| "invalid plan_index")?,
+            start_time: fields[2].parse().map_err(|_| "invalid start_time")?,
+            end_time: fields[3].parse().map_err(|_| "invalid end_time")?,
+        })
+    }
+
+    fn parse_account(hex: &str) -> Result<AccountId, String> {
+        let hex: &str = hex.strip_prefix("0x").unwrap_or(hex);
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err("invalid user".to_string());
+        }
+        let mut bytes: [u8; 32] = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                .map_err(|_| "invalid user".to_string())?;
+        }
+        Ok(AccountId::from(bytes))
+    }
+}
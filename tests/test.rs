@@ -7,15 +7,40 @@ mod utils;
 
 #[cfg(test)]
 pub mod tests {
+    use crate::subscrypt::import::chunk_import_csv;
+    use crate::subscrypt::subscrypt::CharacteristicSpec;
+    use crate::subscrypt::subscrypt::CharacteristicType;
+    use crate::subscrypt::subscrypt::DisputeStatus;
+    use crate::subscrypt::subscrypt::ImportedSubscription;
+    use crate::subscrypt::subscrypt::KeyAlgorithm;
     use crate::subscrypt::subscrypt::LinkedList;
     use crate::subscrypt::subscrypt::PlanConsts;
+    use crate::subscrypt::subscrypt::PlanConstsV1;
+    use crate::subscrypt::subscrypt::PlanKind;
+    use crate::subscrypt::subscrypt::PlanRecordV1;
+    use crate::subscrypt::subscrypt::ProviderV1;
+    use crate::subscrypt::subscrypt::SubscriptionRecordV1;
     use crate::subscrypt::subscrypt::Subscrypt;
+    use crate::subscrypt::subscrypt::User;
+    use crate::subscrypt::subscrypt::UsernameStatus;
+    use crate::subscrypt::subscrypt::Visibility;
+    use crate::subscrypt::subscrypt::LIFETIME_DURATION;
+    use crate::subscrypt::subscrypt::MAX_IMPORT_BATCH_SIZE;
+    use crate::subscrypt::subscrypt::MAX_PERIODS;
+    use crate::subscrypt::subscrypt::STORAGE_VERSION;
     use crate::utils::utils::{
-        set_account_balance, set_caller, subscrypt_add_plan_routine, subscrypt_edit_plan_routine,
+        meter_public_key, set_account_balance, set_caller, sign_usage_report,
+        subscrypt_add_plan_routine, subscrypt_edit_plan_routine,
         subscrypt_provider_register_routine,
     };
+    use core::mem::ManuallyDrop;
     use ink_env::hash::{HashOutput, Sha2x256};
+    use ink_env::AccountId;
     use ink_lang as ink;
+    use ink_primitives::Key;
+    use ink_storage::collections::HashMap;
+    use ink_storage::lazy::LazyCell;
+    use ink_storage::traits::{pull_spread_root, push_spread_root, PackedLayout, SpreadLayout};
 
     #[ink::test]
     fn constructor_works() {
@@ -292,27 +317,13 @@ pub mod tests {
 
         subscrypt.change_disable(1);
         assert_eq!(
-            subscrypt
-                .providers
-                .get(&accounts.alice)
-                .unwrap()
-                .plans
-                .get(1)
-                .unwrap()
-                .disabled,
+            subscrypt.plans.get(&(accounts.alice, 1)).unwrap().disabled,
             true
         );
 
         subscrypt.change_disable(1);
         assert_eq!(
-            subscrypt
-                .providers
-                .get(&accounts.alice)
-                .unwrap()
-                .plans
-                .get(1)
-                .unwrap()
-                .disabled,
+            subscrypt.plans.get(&(accounts.alice, 1)).unwrap().disabled,
             false
         );
     }
@@ -356,6 +367,7 @@ pub mod tests {
             output,
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
@@ -415,6 +427,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
@@ -458,6 +471,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
@@ -507,6 +521,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
     }
 
@@ -543,6 +558,7 @@ pub mod tests {
             [0; 32],
             "alice".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
     }
 
@@ -581,6 +597,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
@@ -635,6 +652,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
@@ -687,14 +705,12 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["value".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -703,11 +719,8 @@ pub mod tests {
         subscrypt.renew(accounts.alice, 1, vec!["value".to_string()]);
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(1)
+                .get(&(accounts.bob, accounts.alice, 1))
                 .unwrap()
                 .provider,
             accounts.alice
@@ -766,14 +779,12 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -782,11 +793,8 @@ pub mod tests {
         subscrypt.refund(accounts.alice, 1);
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             true
@@ -842,14 +850,12 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -893,14 +899,12 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -963,14 +967,12 @@ pub mod tests {
             output,
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -1013,14 +1015,12 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -1059,14 +1059,12 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -1110,14 +1108,12 @@ pub mod tests {
             output,
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -1170,14 +1166,12 @@ pub mod tests {
             output,
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
-                .records
-                .get(&(accounts.bob, accounts.alice))
-                .unwrap()
                 .subscription_records
-                .get(0)
+                .get(&(accounts.bob, accounts.alice, 0))
                 .unwrap()
                 .refunded,
             false
@@ -1258,8 +1252,14 @@ pub mod tests {
             true
         );
 
-        assert_eq!(subscrypt.is_username_available("bobb".to_string()), true);
-        assert_eq!(subscrypt.is_username_available("bob".to_string()), false);
+        assert_eq!(
+            subscrypt.is_username_available("bobb".to_string()),
+            UsernameStatus::Available
+        );
+        assert_eq!(
+            subscrypt.is_username_available("bob".to_string()),
+            UsernameStatus::Taken
+        );
         set_caller(callee, accounts.bob, 0);
         assert_eq!(subscrypt.get_username(), "bob".to_string());
         set_caller(callee, accounts.alice, 0);
@@ -1270,7 +1270,8 @@ pub mod tests {
                 duration: 60 * 60 * 24,
                 price: 10000,
                 max_refund_permille_policy: 50,
-                disabled: false
+                disabled: false,
+                kind: PlanKind::Recurring,
             }
         );
     }
@@ -1314,6 +1315,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         set_caller(callee, accounts.bob, 10000);
 
@@ -1323,6 +1325,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
 
         subscrypt.subscribe(
@@ -1331,6 +1334,7 @@ pub mod tests {
             [0; 32],
             "bob".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         set_caller(callee, accounts.eve, 10000);
 
@@ -1340,6 +1344,7 @@ pub mod tests {
             [0; 32],
             "eve".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         subscrypt.subscribe(
             accounts.alice,
@@ -1347,6 +1352,7 @@ pub mod tests {
             [0; 32],
             "eve".to_string(),
             vec!["nothing important".to_string()],
+            None,
         );
         assert_eq!(
             subscrypt
@@ -1362,4 +1368,4146 @@ pub mod tests {
 
         subscrypt.process(accounts.alice, 1000);
     }
+
+    /// Simple scenario that `alice` register as a provider and makes her monthly plan a team plan
+    /// with 3 seats. `bob` subscribes to it and assigns two seats to `charlie` and `django`, then
+    /// revokes the seat of `charlie` and renews his team subscription for two seats.
+    #[ink::test]
+    fn add_seats_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(callee, 250100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_max_seats(1, 3);
+        assert_eq!(subscrypt.get_plan_max_seats(accounts.alice, 1), 3);
+        assert_eq!(subscrypt.get_plan_max_seats(accounts.alice, 0), 1);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.add_seats(accounts.alice, 1, vec![accounts.charlie, accounts.django]);
+        assert_eq!(
+            subscrypt.get_seat_members(accounts.bob, accounts.alice, 1),
+            vec![accounts.charlie, accounts.django]
+        );
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        assert!(subscrypt.check_subscription(accounts.django, accounts.alice, 1));
+        assert!(!subscrypt.check_subscription(accounts.django, accounts.alice, 0));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            135000
+        );
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.revoke_seat(accounts.alice, 1, accounts.charlie);
+        assert!(!subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        assert_eq!(
+            subscrypt.get_seat_members(accounts.bob, accounts.alice, 1),
+            vec![accounts.django]
+        );
+
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+        assert!(subscrypt.check_subscription(accounts.django, accounts.alice, 1));
+    }
+
+    /// Simple scenario that `bob` subscribes to the monthly team plan of `alice`, assigns two seats
+    /// and renews his team subscription for three seats. The locks of all seats of his first
+    /// subscription are sent to `alice` on renew, so she can not withdraw them again after the end
+    /// of his first subscription.
+    #[ink::test]
+    fn renew_works_team_subscription() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(callee, 300100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_max_seats(1, 3);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.add_seats(accounts.alice, 1, vec![accounts.charlie, accounts.django]);
+
+        set_caller(callee, accounts.bob, 150000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            285000
+        );
+
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        while ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp")
+            < now + 31 * 60 * 60 * 24
+        {
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+        }
+        set_caller(callee, accounts.alice, 0);
+        assert_eq!(subscrypt.withdraw(), 0);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            285000
+        );
+    }
+
+    /// Simple scenario that `bob` refunds his team subscription, so the seat of `charlie` is
+    /// revoked and it is not restored when `bob` subscribes again
+    #[ink::test]
+    fn add_seats_cleared_on_refund() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 250100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_max_seats(1, 3);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.add_seats(accounts.alice, 1, vec![accounts.charlie]);
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.refund(accounts.alice, 1);
+        assert!(!subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        assert_eq!(
+            subscrypt.get_seat_members(accounts.bob, accounts.alice, 1),
+            vec![]
+        );
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert!(!subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        set_caller(callee, accounts.django, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "django".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.django, 50000);
+        subscrypt.add_seats(accounts.alice, 1, vec![accounts.charlie]);
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+    }
+
+    /// Simple scenario that `bob` subscribes to a team plan of `alice` with 2 seats and tries to
+    /// assign two seats to other members so it will fail
+    #[ink::test]
+    #[should_panic(expected = "There is no free seat in this plan!")]
+    fn add_seats_fails_no_free_seat() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 150100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_max_seats(1, 2);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.add_seats(accounts.alice, 1, vec![accounts.charlie, accounts.django]);
+    }
+
+    /// Simple scenario that `alice` register as a provider and makes her daily plan a metered plan
+    /// with price of 10 per unit. `bob` deposits 1000 in his escrow and the meter of `alice` reports
+    /// 20 units of usage signed by its key, then `bob` withdraws the rest of his escrow.
+    #[ink::test]
+    fn report_usage_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+        set_account_balance(callee, 1100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_unit_price(0, 10);
+        subscrypt.set_meter(accounts.charlie, meter_public_key([1; 32]));
+
+        set_caller(callee, accounts.bob, 1000);
+        subscrypt.deposit_escrow(accounts.alice);
+        assert_eq!(subscrypt.get_escrow(accounts.bob, accounts.alice).balance, 1000);
+
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.report_usage(
+            accounts.bob,
+            accounts.alice,
+            0,
+            20,
+            0,
+            sign_usage_report([1; 32], accounts.bob, accounts.alice, 0, 20, 0),
+        );
+        let escrow = subscrypt.get_escrow(accounts.bob, accounts.alice);
+        assert_eq!(escrow.balance, 800);
+        assert_eq!(escrow.nonce, 1);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            200
+        );
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.withdraw_escrow(accounts.alice, 800);
+        assert_eq!(subscrypt.get_escrow(accounts.bob, accounts.alice).balance, 0);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            800
+        );
+    }
+
+    /// Simple scenario that `bob` sets a spend cap of 500 per day on his escrow for `alice` and
+    /// `alice` tries to debit 600 in the same day so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Usage exceeds spend cap of user")]
+    fn report_usage_fails_spend_cap() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_unit_price(0, 10);
+        subscrypt.set_meter(accounts.charlie, meter_public_key([1; 32]));
+
+        set_caller(callee, accounts.bob, 1000);
+        subscrypt.deposit_escrow(accounts.alice);
+        subscrypt.set_spend_cap(accounts.alice, 500, 60 * 60 * 24);
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.report_usage(
+            accounts.bob,
+            accounts.alice,
+            0,
+            20,
+            0,
+            sign_usage_report([1; 32], accounts.bob, accounts.alice, 0, 20, 0),
+        );
+        subscrypt.report_usage(
+            accounts.bob,
+            accounts.alice,
+            0,
+            40,
+            1,
+            sign_usage_report([1; 32], accounts.bob, accounts.alice, 0, 40, 1),
+        );
+    }
+
+    /// Simple scenario that the meter of `alice` signs a usage report of 20 units and `alice`
+    /// submits it with 40 units so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Usage report is not signed by the meter!")]
+    fn report_usage_fails_invalid_signature() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_unit_price(0, 10);
+        subscrypt.set_meter(accounts.charlie, meter_public_key([1; 32]));
+
+        set_caller(callee, accounts.bob, 1000);
+        subscrypt.deposit_escrow(accounts.alice);
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.report_usage(
+            accounts.bob,
+            accounts.alice,
+            0,
+            40,
+            0,
+            sign_usage_report([1; 32], accounts.bob, accounts.alice, 0, 20, 0),
+        );
+    }
+
+    /// Simple scenario that the meter of `alice` reports so many units that their cost overflows
+    /// so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Usage cost overflows!")]
+    fn report_usage_fails_cost_overflow() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_unit_price(0, 10);
+        subscrypt.set_meter(accounts.charlie, meter_public_key([1; 32]));
+
+        set_caller(callee, accounts.bob, 1000);
+        subscrypt.deposit_escrow(accounts.alice);
+
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.report_usage(
+            accounts.bob,
+            accounts.alice,
+            0,
+            u128::MAX,
+            0,
+            sign_usage_report([1; 32], accounts.bob, accounts.alice, 0, u128::MAX, 0),
+        );
+    }
+
+    /// Simple scenario that `bob` sets a spend cap without a period so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Cap period can not be 0!")]
+    fn set_spend_cap_fails_zero_period() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_unit_price(0, 10);
+
+        set_caller(callee, accounts.bob, 1000);
+        subscrypt.deposit_escrow(accounts.alice);
+        subscrypt.set_spend_cap(accounts.alice, 500, 0);
+    }
+
+    /// Simple scenario that the owner of contract sets 3.33% platform fee and `bob` subscribes to
+    /// second plan of `alice`. `alice` will get 45000 - 1498 because of the fee and the owner
+    /// collects 1498 so no money is created or lost by rounding.
+    #[ink::test]
+    fn platform_fee_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        let owner = subscrypt.configs.owner;
+        set_account_balance(callee, 50100);
+        set_caller(callee, owner, 0);
+        subscrypt.set_platform_fee(333);
+        assert_eq!(subscrypt.configs.platform_fee_basis_points, 333);
+
+        set_account_balance(accounts.bob, 0);
+        set_caller(callee, accounts.bob, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.bob,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "bob".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe(
+            accounts.bob,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            43502
+        );
+        assert_eq!(subscrypt.get_platform_balance(), 1498);
+
+        let owner_balance =
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(owner)
+                .expect("Cannot get account balance");
+        set_caller(callee, owner, 0);
+        assert_eq!(subscrypt.withdraw_platform_balance(), 1498);
+        assert_eq!(subscrypt.get_platform_balance(), 0);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(owner)
+                .expect("Cannot get account balance"),
+            owner_balance + 1498
+        );
+    }
+
+    /// Simple scenario that `eve` who is not the owner of contract tries to set platform fee so it
+    /// will fail
+    #[ink::test]
+    #[should_panic(expected = "You are not the owner")]
+    fn set_platform_fee_fails_not_owner() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.eve, 0);
+        subscrypt.set_platform_fee(100);
+    }
+
+    /// Simple scenario that `alice` register as a provider and splits her payouts between herself,
+    /// `charlie` and `django` with 700, 200 and 100 permille shares. `bob` subscribes to her second
+    /// plan and 45009 which is sent to providers is split as 31508, 9001 and 4500 so the dust of
+    /// rounding is paid to `alice`.
+    #[ink::test]
+    fn set_payees_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.charlie, 0);
+        set_account_balance(accounts.django, 0);
+        set_account_balance(callee, 50110);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50010],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_payees(
+            vec![accounts.alice, accounts.charlie, accounts.django],
+            vec![700, 200, 100],
+        );
+        assert_eq!(subscrypt.get_payees(accounts.alice).len(), 3);
+
+        set_caller(callee, accounts.bob, 50010);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            31508
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.charlie)
+                .expect("Cannot get account balance"),
+            9001
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.django)
+                .expect("Cannot get account balance"),
+            4500
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.set_payees(vec![], vec![]);
+        assert_eq!(subscrypt.get_payees(accounts.alice).len(), 0);
+    }
+
+    /// Simple scenario that `alice` tries to set payees with shares that do not sum up to 1000 so
+    /// it will fail
+    #[ink::test]
+    #[should_panic(expected = "Sum of shares should be 1000")]
+    fn set_payees_fails_wrong_shares() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_payees(vec![accounts.alice, accounts.charlie], vec![700, 200]);
+    }
+
+    /// Simple scenario that `alice` splits her payouts between herself and `charlie` but the
+    /// contract does not have enough balance to pay the shares so subscription of `bob` will fail
+    #[ink::test]
+    #[should_panic(expected = "TransferFailed")]
+    fn set_payees_fails_transfer() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 10000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_payees(vec![accounts.alice, accounts.charlie], vec![500, 500]);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `alice` registers `charlie` as her affiliate with 10% commission and
+    /// `bob` subscribes to her second plan referred by `charlie`. From 45000 which is sent to
+    /// provider 4500 is kept for `charlie`. Then `bob` refunds 10% of his payment so 10% of the
+    /// commission is clawed back and sent to `alice`.
+    #[ink::test]
+    fn subscribe_works_with_referrer() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.charlie, 0);
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.register_affiliate(accounts.charlie, 100);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            Some(accounts.charlie),
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            40500
+        );
+        assert_eq!(
+            subscrypt.get_affiliate(accounts.alice, accounts.charlie).earned,
+            4500
+        );
+
+        subscrypt.refund(accounts.alice, 1);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            40950
+        );
+        assert_eq!(
+            subscrypt.get_affiliate(accounts.alice, accounts.charlie).earned,
+            4050
+        );
+
+        set_caller(callee, accounts.charlie, 0);
+        assert_eq!(subscrypt.withdraw_affiliate_earnings(accounts.alice), 4050);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.charlie)
+                .expect("Cannot get account balance"),
+            4050
+        );
+        assert_eq!(
+            subscrypt
+                .get_affiliate(accounts.alice, accounts.charlie)
+                .withdrawable,
+            0
+        );
+    }
+
+    /// Simple scenario that `charlie` withdraws his commission before `bob` refunds, so the claw
+    /// back is recovered from his commission of the next referred subscription of `eve`
+    #[ink::test]
+    fn refund_clawback_works_after_withdraw() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.charlie, 0);
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.register_affiliate(accounts.charlie, 100);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            Some(accounts.charlie),
+        );
+        set_caller(callee, accounts.charlie, 0);
+        assert_eq!(subscrypt.withdraw_affiliate_earnings(accounts.alice), 4500);
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.refund(accounts.alice, 1);
+        assert_eq!(
+            subscrypt.get_affiliate(accounts.alice, accounts.charlie).earned,
+            4050
+        );
+        assert_eq!(
+            subscrypt.get_affiliate_debt(accounts.alice, accounts.charlie),
+            450
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            40500
+        );
+
+        set_caller(callee, accounts.eve, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "eve".to_string(),
+            vec!["nothing important".to_string()],
+            Some(accounts.charlie),
+        );
+        assert_eq!(
+            subscrypt.get_affiliate_debt(accounts.alice, accounts.charlie),
+            0
+        );
+        assert_eq!(
+            subscrypt
+                .get_affiliate(accounts.alice, accounts.charlie)
+                .withdrawable,
+            4050
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            81450
+        );
+    }
+
+    /// Simple scenario that `bob` subscribes to the free plan of `alice` referred by `charlie` and
+    /// refunds it, there is no commission to claw back
+    #[ink::test]
+    fn refund_clawback_works_free_plan() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24 * 30],
+            vec![0],
+            vec![100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()]],
+        );
+        subscrypt.register_affiliate(accounts.charlie, 100);
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.subscribe(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            Some(accounts.charlie),
+        );
+        assert_eq!(subscrypt.refund(accounts.alice, 0), 0);
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 0));
+        assert_eq!(
+            subscrypt
+                .get_affiliate(accounts.alice, accounts.charlie)
+                .earned,
+            0
+        );
+        assert_eq!(
+            subscrypt.get_affiliate_debt(accounts.alice, accounts.charlie),
+            0
+        );
+    }
+
+    /// Simple scenario that `bob` tries to subscribe referred by `eve` who is not an affiliate of
+    /// `alice` so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Referrer is not an affiliate of this provider!")]
+    fn subscribe_fails_referrer_not_affiliate() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            Some(accounts.eve),
+        );
+    }
+
+    /// Simple scenario that `bob` subscribes with username `bob` and changes it to `bobby`. The
+    /// username `bob` is released but it is not available because of the release cooldown.
+    #[ink::test]
+    fn change_username_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        subscrypt.change_username("bobby".to_string());
+        assert_eq!(subscrypt.get_username(), "bobby".to_string());
+        assert_eq!(
+            subscrypt.get_address_by_username("bobby".to_string()),
+            accounts.bob
+        );
+        assert_eq!(
+            subscrypt.is_username_available("bob".to_string()),
+            UsernameStatus::Cooldown
+        );
+        assert_eq!(
+            subscrypt.is_username_available("bobby".to_string()),
+            UsernameStatus::Taken
+        );
+        assert!(subscrypt.check_subscription_with_username("bobby".to_string(), accounts.alice, 1));
+        assert!(!subscrypt.check_subscription_with_username("bob".to_string(), accounts.alice, 1));
+    }
+
+    /// Simple scenario that the owner of contract removes the release cooldown and `alice`
+    /// releases her username so it will be available again
+    #[ink::test]
+    fn release_username_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, subscrypt.configs.owner, 0);
+        subscrypt.set_username_release_cooldown(0);
+
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        assert_eq!(
+            subscrypt.is_username_available("alice".to_string()),
+            UsernameStatus::Taken
+        );
+        subscrypt.release_username();
+        assert_eq!(
+            subscrypt.is_username_available("alice".to_string()),
+            UsernameStatus::Available
+        );
+    }
+
+    /// Simple scenario that `alice` transfers her username to `eve` and `eve` accepts it
+    #[ink::test]
+    fn transfer_username_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.transfer_username(accounts.eve);
+        assert_eq!(
+            subscrypt.get_address_by_username("alice".to_string()),
+            accounts.alice
+        );
+
+        set_caller(callee, accounts.eve, 0);
+        subscrypt.accept_username_transfer(accounts.alice);
+        assert_eq!(subscrypt.get_username(), "alice".to_string());
+        assert_eq!(
+            subscrypt.get_address_by_username("alice".to_string()),
+            accounts.eve
+        );
+    }
+
+    /// Simple scenario that `bob` changes his username and `eve` tries to subscribe with his old
+    /// username before the release cooldown is passed so it will fail
+    #[ink::test]
+    #[should_panic(expected = "username is invalid")]
+    fn subscribe_fails_released_username() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        subscrypt.change_username("bobby".to_string());
+
+        set_caller(callee, accounts.eve, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that the owner of contract changes the username rules and reserves `admin`,
+    /// then `is_username_available` returns the reason of unavailable usernames
+    #[ink::test]
+    fn is_username_available_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, subscrypt.configs.owner, 0);
+        subscrypt.set_username_length_limits(4, 8);
+        subscrypt.add_reserved_usernames(vec!["Admin".to_string(), "root".to_string()]);
+        subscrypt.remove_reserved_usernames(vec!["ROOT".to_string()]);
+
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "Alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        assert_eq!(
+            subscrypt.get_address_by_username("alice".to_string()),
+            accounts.alice
+        );
+        assert_eq!(
+            subscrypt.is_username_available("ALICE".to_string()),
+            UsernameStatus::Taken
+        );
+        assert_eq!(
+            subscrypt.is_username_available("bob".to_string()),
+            UsernameStatus::TooShort
+        );
+        assert_eq!(
+            subscrypt.is_username_available("bobbybobby".to_string()),
+            UsernameStatus::TooLong
+        );
+        assert_eq!(
+            subscrypt.is_username_available("bob by".to_string()),
+            UsernameStatus::InvalidCharacter
+        );
+        assert_eq!(
+            subscrypt.is_username_available("ADMIN".to_string()),
+            UsernameStatus::Reserved
+        );
+        assert_eq!(
+            subscrypt.is_username_available("root".to_string()),
+            UsernameStatus::Available
+        );
+    }
+
+    /// Simple scenario that `bob` subscribes with `Bob` and uses `bob` to check his subscription
+    #[ink::test]
+    fn subscribe_username_case_insensitive_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "Bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert_eq!(subscrypt.get_username(), "bob".to_string());
+        assert!(subscrypt.check_subscription_with_username("bob".to_string(), accounts.alice, 1));
+        assert!(subscrypt.check_subscription_with_username("BOB".to_string(), accounts.alice, 1));
+    }
+
+    /// Simple scenario that `alice` tries to register with a reserved username so it will fail
+    #[ink::test]
+    #[should_panic(expected = "this username is invalid")]
+    fn provider_register_fails_reserved_username() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, subscrypt.configs.owner, 0);
+        subscrypt.add_reserved_usernames(vec!["subscrypt".to_string()]);
+
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "SubScrypt".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+    }
+
+    /// Simple scenario that `bob` tries to subscribe with an empty username so it will fail
+    #[ink::test]
+    #[should_panic(expected = "username is invalid")]
+    fn subscribe_fails_empty_username() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `alice` sets her encryption key, `bob` subscribes and then `alice`
+    /// rotates the key. `bob` subscription record keeps the version of the first key.
+    #[ink::test]
+    fn set_encryption_key_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_encryption_key(KeyAlgorithm::X25519, vec![1; 32]);
+        assert_eq!(subscrypt.get_encryption_key(accounts.alice).version, 1);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.set_encryption_key(KeyAlgorithm::Sr25519, vec![2; 32]);
+        let key = subscrypt.get_encryption_key(accounts.alice);
+        assert_eq!(key.algorithm, KeyAlgorithm::Sr25519);
+        assert_eq!(key.public_key, vec![2; 32]);
+        assert_eq!(key.version, 2);
+
+        set_caller(callee, accounts.bob, 0);
+        assert_eq!(
+            subscrypt.retrieve_data_with_wallet(accounts.alice)[0].key_version,
+            1
+        );
+    }
+
+    /// Simple scenario that `bob` which is not a provider tries to set an encryption key
+    #[ink::test]
+    #[should_panic(expected = "You should first register in the contract!")]
+    fn set_encryption_key_fails_not_provider() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.set_encryption_key(KeyAlgorithm::X25519, vec![1; 32]);
+    }
+
+    /// Simple scenario that `bob` subscribes and updates his characteristics values in the middle
+    /// of his subscription
+    #[ink::test]
+    fn update_characteristics_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["old address".to_string()],
+            None,
+        );
+        subscrypt.update_characteristics(accounts.alice, 1, vec!["new address".to_string()]);
+        assert_eq!(
+            subscrypt.get_user_plan_characteristics(accounts.bob, accounts.alice, 1),
+            vec!["new address".to_string()]
+        );
+        assert_eq!(
+            subscrypt.retrieve_data_with_wallet(accounts.alice)[0].characteristics_version,
+            1
+        );
+    }
+
+    /// Simple scenario that `bob` tries to update his characteristics with wrong number of values
+    #[ink::test]
+    #[should_panic(expected = "invalid characteristic values!")]
+    fn update_characteristics_fails_invalid_values() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["old address".to_string()],
+            None,
+        );
+        subscrypt.update_characteristics(accounts.alice, 1, vec![]);
+    }
+
+    /// Simple scenario that `alice` adds typed characteristics to her plan, reorders and removes
+    /// them and then `bob` subscribes with the last version of schema
+    #[ink::test]
+    fn set_characteristic_schema_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["name".to_string()]],
+        );
+        subscrypt.add_characteristic_for_plan(1, vec!["email".to_string(), "tier".to_string()]);
+        subscrypt.set_characteristic_schema(
+            1,
+            vec![
+                CharacteristicSpec {
+                    kind: CharacteristicType::Text,
+                    required: true,
+                    max_size: 64,
+                },
+                CharacteristicSpec {
+                    kind: CharacteristicType::Email,
+                    required: false,
+                    max_size: 0,
+                },
+                CharacteristicSpec {
+                    kind: CharacteristicType::Enum(vec!["basic".to_string(), "pro".to_string()]),
+                    required: true,
+                    max_size: 0,
+                },
+            ],
+        );
+        subscrypt.reorder_characteristics_for_plan(1, vec![2, 0, 1]);
+        subscrypt.remove_characteristic_for_plan(1, 2);
+        assert_eq!(
+            subscrypt.get_plan_characteristics(accounts.alice, 1),
+            vec!["tier".to_string(), "name".to_string()]
+        );
+        let schema = subscrypt.get_characteristic_schema(accounts.alice, 1);
+        assert_eq!(schema.version, 4);
+        assert_eq!(schema.specs.len(), 2);
+        assert!(schema.specs[1].required);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["1".to_string(), "encrypted name".to_string()],
+            None,
+        );
+        assert_eq!(
+            subscrypt.retrieve_data_with_wallet(accounts.alice)[0].schema_version,
+            4
+        );
+    }
+
+    /// Simple scenario that `charlie` subscribes with a valid email and number to a plan of
+    /// `alice` which has no encryption key, but the email of `bob` is not valid
+    #[ink::test]
+    #[should_panic(expected = "invalid characteristic values!")]
+    fn subscribe_fails_invalid_email_characteristic() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["email".to_string()]],
+        );
+        subscrypt.add_characteristic_for_plan(1, vec!["seats".to_string()]);
+        subscrypt.set_characteristic_schema(
+            1,
+            vec![
+                CharacteristicSpec {
+                    kind: CharacteristicType::Email,
+                    required: true,
+                    max_size: 0,
+                },
+                CharacteristicSpec {
+                    kind: CharacteristicType::Number,
+                    required: true,
+                    max_size: 0,
+                },
+            ],
+        );
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["charlie@example.com".to_string(), "12.5".to_string()],
+            None,
+        );
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["bob".to_string(), "3".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `alice` sets an enum characteristic without any option so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Enum characteristic needs options!")]
+    fn set_characteristic_schema_fails_empty_enum() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["tier".to_string()]],
+        );
+        subscrypt.set_characteristic_schema(
+            1,
+            vec![CharacteristicSpec {
+                kind: CharacteristicType::Enum(vec![]),
+                required: true,
+                max_size: 0,
+            }],
+        );
+    }
+
+    /// Simple scenario that `bob` selects an option that does not exist in an enum characteristic
+    #[ink::test]
+    #[should_panic(expected = "invalid characteristic values!")]
+    fn subscribe_fails_invalid_enum_characteristic() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["tier".to_string()]],
+        );
+        subscrypt.set_characteristic_schema(
+            1,
+            vec![CharacteristicSpec {
+                kind: CharacteristicType::Enum(vec!["basic".to_string(), "pro".to_string()]),
+                required: true,
+                max_size: 0,
+            }],
+        );
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["2".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `alice` registers three plans, sets metadata of them, makes the second
+    /// plan unlisted and disables the third one, so only the first plan is in her catalog
+    #[ink::test]
+    fn get_provider_catalog_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30, 60 * 60 * 24 * 365],
+            vec![10000, 50000, 500000],
+            vec![50, 100, 100],
+            "alice".to_string(),
+            vec![
+                vec!["key".to_string()],
+                vec!["key".to_string()],
+                vec!["key".to_string()],
+            ],
+        );
+        subscrypt.set_plan_metadata(
+            0,
+            "Daily".to_string(),
+            "Access for one day".to_string(),
+            Some("ipfs://daily".to_string()),
+            Some([1; 32]),
+            Visibility::Public,
+        );
+        subscrypt.set_plan_metadata(
+            1,
+            "Partners".to_string(),
+            "Monthly plan of partners".to_string(),
+            None,
+            None,
+            Visibility::Unlisted,
+        );
+        subscrypt.change_disable(2);
+
+        let catalog = subscrypt.get_provider_catalog(accounts.alice);
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].plan_index, 0);
+        assert_eq!(catalog[0].metadata.name, "Daily".to_string());
+        assert_eq!(catalog[0].metadata.uri, Some("ipfs://daily".to_string()));
+        assert_eq!(catalog[0].characteristics, vec!["key".to_string()]);
+        assert_eq!(
+            subscrypt.get_plan_metadata(accounts.alice, 1).visibility,
+            Visibility::Unlisted
+        );
+    }
+
+    /// Simple scenario that `alice` tries to set metadata of a plan that does not exist
+    #[ink::test]
+    #[should_panic(expected = "please select a valid plan")]
+    fn set_plan_metadata_fails_wrong_plan() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_metadata(
+            2,
+            "Yearly".to_string(),
+            "".to_string(),
+            None,
+            None,
+            Visibility::Public,
+        );
+    }
+
+    /// Simple scenario that `alice`, `charlie` and `eve` register with profiles and the directory
+    /// is listed with and without category filter
+    #[ink::test]
+    fn list_providers_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_provider_profile(
+            "Alice Videos".to_string(),
+            "".to_string(),
+            "https://alice.example".to_string(),
+            None,
+            "video".to_string(),
+        );
+        set_caller(callee, accounts.charlie, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.charlie,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "charlie".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_provider_profile(
+            "Charlie Music".to_string(),
+            "".to_string(),
+            "https://charlie.example".to_string(),
+            None,
+            "music".to_string(),
+        );
+        set_caller(callee, accounts.eve, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.eve,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "eve".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_provider_profile(
+            "Eve Videos".to_string(),
+            "".to_string(),
+            "https://eve.example".to_string(),
+            None,
+            "video".to_string(),
+        );
+
+        let videos = subscrypt.list_providers(Some("video".to_string()), 0, 10);
+        assert_eq!(videos.listings.len(), 2);
+        assert_eq!(videos.listings[0].provider, accounts.alice);
+        assert_eq!(videos.listings[0].username, "alice".to_string());
+        assert_eq!(videos.listings[1].provider, accounts.eve);
+        assert_eq!(videos.listings[1].registration_index, 2);
+        assert_eq!(
+            videos.listings[1].profile.display_name,
+            "Eve Videos".to_string()
+        );
+        assert_eq!(videos.next_start, None);
+
+        let page = subscrypt.list_providers(None, 1, 1);
+        assert_eq!(page.listings.len(), 1);
+        assert_eq!(page.listings[0].provider, accounts.charlie);
+        assert_eq!(page.next_start, Some(2));
+        let page = subscrypt.list_providers(Some("video".to_string()), 0, 1);
+        assert_eq!(page.listings[0].provider, accounts.alice);
+        let page = subscrypt.list_providers(Some("video".to_string()), page.next_start.unwrap(), 1);
+        assert_eq!(page.listings[0].provider, accounts.eve);
+        assert_eq!(page.next_start, None);
+        assert_eq!(
+            subscrypt.get_provider_profile(accounts.charlie).category,
+            "music".to_string()
+        );
+    }
+
+    /// Simple scenario that `bob` which is not a provider tries to set a provider profile
+    #[ink::test]
+    #[should_panic(expected = "You should first register in the contract!")]
+    fn set_provider_profile_fails_not_provider() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.set_provider_profile(
+            "Bob".to_string(),
+            "".to_string(),
+            "".to_string(),
+            None,
+            "video".to_string(),
+        );
+    }
+
+    /// Simple scenario that `alice` makes her second plan invite-only and adds `bob` to its
+    /// allowlist so `bob` can subscribe
+    #[ink::test]
+    fn allowlist_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_metadata(
+            1,
+            "B2B".to_string(),
+            "Monthly plan of approved companies".to_string(),
+            None,
+            None,
+            Visibility::InviteOnly,
+        );
+        subscrypt.add_to_allowlist(1, vec![accounts.bob, accounts.charlie]);
+        subscrypt.remove_from_allowlist(1, vec![accounts.charlie]);
+        assert!(subscrypt.is_allowlisted(accounts.alice, 1, accounts.bob));
+        assert!(!subscrypt.is_allowlisted(accounts.alice, 1, accounts.charlie));
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+    }
+
+    /// Simple scenario that `charlie` which is not in the allowlist tries to subscribe to an
+    /// invite-only plan
+    #[ink::test]
+    #[should_panic(expected = "You are not in the allowlist of this plan!")]
+    fn subscribe_fails_not_in_allowlist() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_metadata(
+            1,
+            "B2B".to_string(),
+            "Monthly plan of approved companies".to_string(),
+            None,
+            None,
+            Visibility::InviteOnly,
+        );
+        subscrypt.add_to_allowlist(1, vec![accounts.bob]);
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `alice` sets merkle root of `bob` and `charlie` for her invite-only
+    /// plan and `bob` proves his membership and subscribes
+    #[ink::test]
+    fn submit_allowlist_proof_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_metadata(
+            1,
+            "B2B".to_string(),
+            "Monthly plan of approved companies".to_string(),
+            None,
+            None,
+            Visibility::InviteOnly,
+        );
+        let mut bob_leaf = <Sha2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Sha2x256, _>(&accounts.bob, &mut bob_leaf);
+        let mut charlie_leaf = <Sha2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Sha2x256, _>(&accounts.charlie, &mut charlie_leaf);
+        let mut pair: Vec<u8> = Vec::new();
+        if bob_leaf <= charlie_leaf {
+            pair.extend_from_slice(&bob_leaf);
+            pair.extend_from_slice(&charlie_leaf);
+        } else {
+            pair.extend_from_slice(&charlie_leaf);
+            pair.extend_from_slice(&bob_leaf);
+        }
+        let mut root = <Sha2x256 as HashOutput>::Type::default();
+        ink_env::hash_bytes::<Sha2x256>(&pair, &mut root);
+        subscrypt.set_allowlist_merkle_root(1, Some(root));
+        assert_eq!(
+            subscrypt.get_allowlist_merkle_root(accounts.alice, 1),
+            Some(root)
+        );
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.submit_allowlist_proof(accounts.alice, 1, vec![charlie_leaf]);
+        assert!(subscrypt.is_allowlisted(accounts.alice, 1, accounts.bob));
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.set_allowlist_merkle_root(1, Some(charlie_leaf));
+        assert!(!subscrypt.is_allowlisted(accounts.alice, 1, accounts.bob));
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.submit_allowlist_proof(accounts.alice, 1, vec![]);
+        assert!(subscrypt.is_allowlisted(accounts.alice, 1, accounts.charlie));
+    }
+
+    /// Simple scenario that `alice` removes `bob` from the allowlist after he proved his
+    /// membership, so he can not add himself again with the same merkle root
+    #[ink::test]
+    #[should_panic(expected = "You are removed from the allowlist of this plan!")]
+    fn submit_allowlist_proof_fails_removed() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        let mut bob_leaf = <Sha2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Sha2x256, _>(&accounts.bob, &mut bob_leaf);
+        subscrypt.set_allowlist_merkle_root(1, Some(bob_leaf));
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.submit_allowlist_proof(accounts.alice, 1, vec![]);
+        assert!(subscrypt.is_allowlisted(accounts.alice, 1, accounts.bob));
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.remove_from_allowlist(1, vec![accounts.bob]);
+        assert!(!subscrypt.is_allowlisted(accounts.alice, 1, accounts.bob));
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.submit_allowlist_proof(accounts.alice, 1, vec![]);
+    }
+
+    /// Simple scenario that `alice` limits her second plan to one subscriber. `bob` subscribes,
+    /// `charlie` joins the waitlist and gets the right to subscribe when `bob` refunds, so `eve`
+    /// can not subscribe and joins the waitlist after him.
+    #[ink::test]
+    fn waitlist_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1000000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_capacity(1, 1, true, 60 * 60);
+        assert_eq!(subscrypt.get_plan_capacity(accounts.alice, 1).max_active, 1);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 1), 1);
+
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.join_waitlist(accounts.alice, 1);
+        assert_eq!(
+            subscrypt.get_waitlist(accounts.alice, 1),
+            vec![accounts.charlie]
+        );
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.refund(accounts.alice, 1);
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 1), 0);
+        assert!(subscrypt
+            .get_waitlist_offer(accounts.alice, 1, accounts.charlie)
+            .is_some());
+
+        set_caller(callee, accounts.eve, 0);
+        subscrypt.join_waitlist(accounts.alice, 1);
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        assert_eq!(
+            subscrypt.get_waitlist(accounts.alice, 1),
+            vec![accounts.eve]
+        );
+    }
+
+    /// Simple scenario that the subscription of `bob` to a full plan expires, so `charlie` who
+    /// joined the waitlist gets an offer after the end of its last day
+    #[ink::test]
+    fn waitlist_works_after_expiry() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1000000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_capacity(0, 1, true, 60 * 60);
+
+        set_caller(callee, accounts.bob, 10000);
+        subscrypt.subscribe(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.join_waitlist(accounts.alice, 0);
+        assert_eq!(
+            subscrypt.get_waitlist(accounts.alice, 0),
+            vec![accounts.charlie]
+        );
+
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        while ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp")
+            < now + 2 * 60 * 60 * 24
+        {
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+        }
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 0), 0);
+        subscrypt.process_waitlist(accounts.alice, 0);
+        assert!(subscrypt
+            .get_waitlist_offer(accounts.alice, 0, accounts.charlie)
+            .is_some());
+        assert!(subscrypt.get_waitlist(accounts.alice, 0).is_empty());
+    }
+
+    /// Simple scenario that the plan of `alice` is not used for 40 days after `bob` subscribes to
+    /// one day and `charlie` to 36 days of it, so the expiries are processed in two calls
+    #[ink::test]
+    fn active_count_works_after_idle_days() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1000000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_capacity(0, 2, true, 60 * 60);
+
+        set_caller(callee, accounts.bob, 10000);
+        subscrypt.subscribe(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.charlie, 360000);
+        subscrypt.subscribe_periods(
+            accounts.alice,
+            0,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            36,
+        );
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 0), 2);
+
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        while ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp")
+            < now + 40 * 60 * 60 * 24
+        {
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+        }
+        // only the expiry of `bob` is in the first `MAX_EXPIRY_DAYS` days
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 0), 1);
+        subscrypt.process_waitlist(accounts.alice, 0);
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 0), 0);
+        subscrypt.process_waitlist(accounts.alice, 0);
+        assert_eq!(subscrypt.get_active_count(accounts.alice, 0), 0);
+    }
+
+    /// Simple scenario that `charlie` tries to subscribe to a full plan without waitlist
+    #[ink::test]
+    #[should_panic(expected = "Plan is full!")]
+    fn subscribe_fails_plan_is_full() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1000000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_capacity(1, 1, false, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `charlie` tries to subscribe to a full plan with waitlist instead of
+    /// joining its waitlist so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Plan is full!")]
+    fn subscribe_fails_plan_is_full_with_waitlist() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1000000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_capacity(1, 1, true, 60 * 60);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Simple scenario that `bob` tries to join the waitlist of a plan which is not full so it
+    /// will fail
+    #[ink::test]
+    #[should_panic(expected = "Plan is not full!")]
+    fn join_waitlist_fails_plan_is_not_full() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 1000000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_plan_capacity(1, 1, true, 60 * 60);
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.join_waitlist(accounts.alice, 1);
+    }
+
+    /// Simple scenario that `alice` schedules a price change of her second plan with
+    /// grandfathering. After the effective time `charlie` subscribes with the new price and `bob`
+    /// renews with his previous price.
+    #[ink::test]
+    fn schedule_plan_edit_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        subscrypt.schedule_plan_edit(
+            1,
+            PlanConsts {
+                duration: 60 * 60 * 24 * 30,
+                price: 60000,
+                max_refund_permille_policy: 100,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            },
+            now + 1,
+            true,
+        );
+        assert_eq!(
+            subscrypt
+                .get_scheduled_edit(accounts.alice, 1)
+                .unwrap()
+                .plan
+                .price,
+            60000
+        );
+        assert_eq!(subscrypt.get_plan_data(accounts.alice, 1).price, 50000);
+        assert_eq!(
+            subscrypt.get_provider_catalog(accounts.alice)[1].plan.price,
+            50000
+        );
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+        assert_eq!(subscrypt.get_plan_data(accounts.alice, 1).price, 60000);
+        assert_eq!(
+            subscrypt.get_provider_catalog(accounts.alice)[1].plan.price,
+            60000
+        );
+
+        set_caller(callee, accounts.charlie, 60000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "charlie".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+        assert_eq!(
+            subscrypt.retrieve_data_with_wallet(accounts.alice)[1]
+                .plan
+                .price,
+            50000
+        );
+    }
+
+    /// Simple scenario that `alice` edits the price of her second plan without grandfathering and
+    /// then schedules another price change with grandfathering, so `bob` renews with the price of
+    /// the first edit
+    #[ink::test]
+    fn schedule_plan_edit_works_after_edit() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.edit_plan(1, 60 * 60 * 24 * 30, 55000, 100, false);
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        subscrypt.schedule_plan_edit(
+            1,
+            PlanConsts {
+                duration: 60 * 60 * 24 * 30,
+                price: 60000,
+                max_refund_permille_policy: 100,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            },
+            now + 1,
+            true,
+        );
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+        assert_eq!(subscrypt.get_plan_data(accounts.alice, 1).price, 60000);
+
+        set_caller(callee, accounts.bob, 55000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+        let records = subscrypt.retrieve_data_with_wallet(accounts.alice);
+        assert_eq!(records[1].plan.price, 55000);
+    }
+
+    /// Simple scenario that `alice` schedules a price change of her second plan without
+    /// grandfathering so `bob` can not renew with his previous price
+    #[ink::test]
+    #[should_panic(expected = "You have to pay exact plan price")]
+    fn renew_fails_scheduled_edit_without_grandfathering() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200000);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        subscrypt.schedule_plan_edit(
+            1,
+            PlanConsts {
+                duration: 60 * 60 * 24 * 30,
+                price: 60000,
+                max_refund_permille_policy: 100,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            },
+            now + 1,
+            false,
+        );
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+    }
+
+    /// Simple scenario that `bob` subscribes to three days of the first plan of `alice` with 10%
+    /// discount and refunds immediately, so he gets back the locked part of all three days
+    #[ink::test]
+    fn subscribe_periods_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 27100);
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_discount_tiers(0, vec![3, 6], vec![100, 200]);
+        assert_eq!(subscrypt.get_discount_tiers(accounts.alice, 0).len(), 2);
+        assert_eq!(subscrypt.get_periods_price(accounts.alice, 0, 3), 27000);
+        assert_eq!(subscrypt.get_periods_price(accounts.alice, 0, 7), 56000);
+
+        set_caller(callee, accounts.bob, 27000);
+        subscrypt.subscribe_periods(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            3,
+        );
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 0));
+        let record = &subscrypt.retrieve_data_with_wallet(accounts.alice)[0];
+        assert_eq!(record.periods, 3);
+        assert_eq!(record.plan.duration, 60 * 60 * 24 * 3);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            25650
+        );
+
+        assert_eq!(subscrypt.refund(accounts.alice, 0), 1350000);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            1350
+        );
+    }
+
+    /// Simple scenario that `bob` pays the price of three days without discount so it will fail
+    #[ink::test]
+    #[should_panic(expected = "You have to pay exact plan price")]
+    fn subscribe_periods_fails_insufficient_paying() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 27100);
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_discount_tiers(0, vec![3, 6], vec![100, 200]);
+
+        set_caller(callee, accounts.bob, 30000);
+        subscrypt.subscribe_periods(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            3,
+        );
+    }
+
+    /// Simple scenario that `bob` subscribes to three days of the first plan of `alice` with 10%
+    /// discount and `alice` raises the price with grandfathering, so `bob` renews with the price
+    /// of the plan without the discount
+    #[ink::test]
+    fn subscribe_periods_renew_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.set_discount_tiers(0, vec![3], vec![100]);
+
+        set_caller(callee, accounts.bob, 27000);
+        subscrypt.subscribe_periods(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            3,
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        subscrypt.schedule_plan_edit(
+            0,
+            PlanConsts {
+                duration: 60 * 60 * 24,
+                price: 12000,
+                max_refund_permille_policy: 50,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            },
+            now + 1,
+            true,
+        );
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+
+        set_caller(callee, accounts.bob, 10000);
+        subscrypt.renew(accounts.alice, 0, vec!["nothing important".to_string()]);
+        let records = subscrypt.retrieve_data_with_wallet(accounts.alice);
+        assert_eq!(records[1].plan.price, 10000);
+        assert_eq!(records[1].plan.duration, 60 * 60 * 24);
+    }
+
+    /// Simple scenario that `bob` subscribes to more than `MAX_PERIODS` periods so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Wrong number of periods!")]
+    fn subscribe_periods_fails_too_many_periods() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+
+        let periods: u64 = MAX_PERIODS + 1;
+        set_caller(callee, accounts.bob, 10000 * u128::from(periods));
+        subscrypt.subscribe_periods(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            periods,
+        );
+    }
+
+    /// Simple scenario that `alice` adds a lifetime plan and `bob` subscribes to it, the
+    /// subscription never expires and `bob` refunds it in the refund window
+    #[ink::test]
+    fn lifetime_plan_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_lifetime_plan(100000, 100, 60 * 60 * 24 * 30, vec!["key".to_string()]);
+        assert_eq!(subscrypt.get_plan_length(accounts.alice), 3);
+        assert_eq!(
+            subscrypt.get_plan_data(accounts.alice, 2).duration,
+            LIFETIME_DURATION
+        );
+        assert_eq!(
+            subscrypt.get_plan_data(accounts.alice, 2).kind,
+            PlanKind::Lifetime
+        );
+        assert_eq!(
+            subscrypt.get_lifetime_refund_window(accounts.alice, 2),
+            Some(60 * 60 * 24 * 30)
+        );
+        assert_eq!(
+            subscrypt.get_lifetime_refund_window(accounts.alice, 1),
+            None
+        );
+
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.subscribe(
+            accounts.alice,
+            2,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 2));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            90000
+        );
+
+        assert_eq!(subscrypt.refund(accounts.alice, 2), 10000000);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            10000
+        );
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 2));
+    }
+
+    /// Simple scenario that `bob` tries to renew his lifetime subscription so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Lifetime subscriptions can not be renewed!")]
+    fn renew_fails_lifetime_subscription() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_lifetime_plan(100000, 100, 60 * 60 * 24 * 30, vec!["key".to_string()]);
+
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.subscribe(
+            accounts.alice,
+            2,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.renew(accounts.alice, 2, vec!["nothing important".to_string()]);
+    }
+
+    /// Simple scenario that `alice` edits her recurring plan to the lifetime duration so it will
+    /// fail
+    #[ink::test]
+    #[should_panic(expected = "Wrong plan duration!")]
+    fn edit_plan_fails_lifetime_duration() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.edit_plan(1, LIFETIME_DURATION, 50000, 100, false);
+    }
+
+    /// Simple scenario that `alice` schedules an edit which changes her lifetime plan to a
+    /// recurring one so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Plan kind can not be changed!")]
+    fn schedule_plan_edit_fails_plan_kind() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_lifetime_plan(100000, 100, 60 * 60 * 24 * 30, vec!["key".to_string()]);
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        subscrypt.schedule_plan_edit(
+            2,
+            PlanConsts {
+                duration: 60 * 60 * 24 * 30,
+                price: 100000,
+                max_refund_permille_policy: 100,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            },
+            now + 1,
+            false,
+        );
+    }
+
+    /// Simple scenario that `alice` creates a bundle of her plan and a plan of `bob`, `charlie`
+    /// subscribes to the bundle after `bob` accepts it and then refunds both subscriptions together
+    #[ink::test]
+    fn bundle_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50200);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.bob,
+            vec![60 * 60 * 24 * 30],
+            vec![40000],
+            vec![100],
+            "bob".to_string(),
+            vec![vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+        set_account_balance(accounts.charlie, 0);
+
+        set_caller(callee, accounts.alice, 0);
+        let bundle_index = subscrypt.create_bundle(
+            vec![(accounts.alice, 1), (accounts.bob, 0)],
+            vec![600, 400],
+            60 * 60 * 24 * 30,
+            50000,
+            200,
+        );
+        assert_eq!(bundle_index, 0);
+        let bundle = subscrypt.get_bundle(0).unwrap();
+        assert_eq!(bundle.creator, accounts.alice);
+        assert!(bundle.plans[0].accepted);
+        assert!(!bundle.plans[1].accepted);
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.accept_bundle(0);
+        assert!(subscrypt.get_bundle(0).unwrap().plans[1].accepted);
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe_bundle(
+            0,
+            [0; 32],
+            "charlie".to_string(),
+            vec![
+                vec!["nothing important".to_string()],
+                vec!["nothing important".to_string()],
+            ],
+        );
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.bob, 0));
+        assert_eq!(
+            subscrypt.retrieve_data_with_wallet(accounts.bob)[0].bundle_index,
+            Some(0)
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            24000
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            16000
+        );
+
+        assert_eq!(subscrypt.refund(accounts.bob, 0), 10000000);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.charlie)
+                .expect("Cannot get account balance"),
+            10000
+        );
+        assert!(!subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        assert!(!subscrypt.check_subscription(accounts.charlie, accounts.bob, 0));
+    }
+
+    /// Simple scenario that `charlie` subscribes to a bundle which is not accepted by `bob` so it
+    /// will fail
+    #[ink::test]
+    #[should_panic(expected = "Bundle is not accepted by all providers!")]
+    fn subscribe_bundle_fails_not_accepted() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50200);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_caller(callee, accounts.bob, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.bob,
+            vec![60 * 60 * 24 * 30],
+            vec![40000],
+            vec![100],
+            "bob".to_string(),
+            vec![vec!["key".to_string()]],
+        );
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.create_bundle(
+            vec![(accounts.alice, 1), (accounts.bob, 0)],
+            vec![600, 400],
+            60 * 60 * 24 * 30,
+            50000,
+            200,
+        );
+
+        set_caller(callee, accounts.charlie, 50000);
+        subscrypt.subscribe_bundle(
+            0,
+            [0; 32],
+            "charlie".to_string(),
+            vec![
+                vec!["nothing important".to_string()],
+                vec!["nothing important".to_string()],
+            ],
+        );
+    }
+
+    /// Simple scenario that `bob` purchases the add-on of the plan of `alice` on top of his
+    /// subscription and then refunds them together
+    #[ink::test]
+    fn add_on_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_add_on(1, "storage".to_string(), 20000);
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert_eq!(subscrypt.get_add_ons(accounts.alice, 1).len(), 1);
+        assert!(!subscrypt.check_subscription_with_add_on(accounts.bob, accounts.alice, 1, 0));
+
+        set_caller(callee, accounts.bob, 25000);
+        subscrypt.purchase_add_on(accounts.alice, 1, 0);
+        assert!(subscrypt.check_subscription_with_add_on(accounts.bob, accounts.alice, 1, 0));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            63000
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            5000
+        );
+
+        assert_eq!(subscrypt.refund(accounts.alice, 1), 7000000);
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            12000
+        );
+        assert!(!subscrypt.check_subscription_with_add_on(accounts.bob, accounts.alice, 1, 0));
+    }
+
+    /// Simple scenario that `bob` renews his subscription alongside its add-on
+    #[ink::test]
+    fn renew_with_add_on_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_add_on(1, "storage".to_string(), 20000);
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 20000);
+        subscrypt.purchase_add_on(accounts.alice, 1, 0);
+
+        set_caller(callee, accounts.bob, 70000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+        assert_eq!(subscrypt.retrieve_data_with_wallet(accounts.alice).len(), 2);
+        assert!(subscrypt.check_subscription_with_add_on(accounts.bob, accounts.alice, 1, 0));
+    }
+
+    /// Simple scenario that `bob` renews his subscription without paying the price of its add-on
+    /// so it will fail
+    #[ink::test]
+    #[should_panic(expected = "You have to pay exact plan price")]
+    fn renew_fails_without_add_on_price() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_add_on(1, "storage".to_string(), 20000);
+        set_account_balance(accounts.alice, 0);
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 20000);
+        subscrypt.purchase_add_on(accounts.alice, 1, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+    }
+
+    /// Simple scenario that `alice` revokes the subscription of `bob` and `bob` is refunded
+    #[ink::test]
+    fn revoke_subscription_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+
+        set_caller(callee, accounts.alice, 0);
+        assert_eq!(subscrypt.revoke_subscription(accounts.bob, 1, 2), 5000000);
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            5000
+        );
+    }
+
+    /// Simple scenario that `alice` revokes the renewed subscription of `bob` which is not started
+    /// yet, so `bob` is refunded the whole locked amount of it
+    #[ink::test]
+    fn revoke_subscription_works_after_renew() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+
+        set_caller(callee, accounts.alice, 0);
+        assert_eq!(subscrypt.revoke_subscription(accounts.bob, 1, 2), 5000000);
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            5000
+        );
+    }
+
+    /// Simple scenario that `alice` revokes the lifetime subscription of `bob` after its refund
+    /// window, its locked amount is already released so nothing is paid
+    #[ink::test]
+    fn revoke_subscription_works_after_refund_window() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.add_lifetime_plan(100000, 100, 60, vec!["key".to_string()]);
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 100000);
+        subscrypt.subscribe(
+            accounts.alice,
+            2,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+        while ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp")
+            < now + 60
+        {
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+        }
+
+        set_caller(callee, accounts.alice, 0);
+        assert_eq!(subscrypt.revoke_subscription(accounts.bob, 2, 2), 0);
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 2));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            0
+        );
+    }
+
+    /// Simple scenario that `alice` revokes the subscription of `bob` who is not subscribed so
+    /// it will fail
+    #[ink::test]
+    #[should_panic(expected = "User is not subscribed to this plan!")]
+    fn revoke_subscription_fails_not_subscribed() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.revoke_subscription(accounts.bob, 1, 2);
+    }
+
+    /// Simple scenario that `alice` grants a complimentary subscription to `charlie` while `bob`
+    /// pays for his subscription, and they are counted separately in plan analytics
+    #[ink::test]
+    fn grant_subscription_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.grant_subscription(
+            accounts.charlie,
+            1,
+            60 * 60 * 24 * 10,
+            vec!["nothing important".to_string()],
+        );
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 1));
+        set_caller(callee, accounts.charlie, 0);
+        let record = &subscrypt.retrieve_data_with_wallet(accounts.alice)[0];
+        assert!(record.complimentary);
+        assert_eq!(record.plan.duration, 60 * 60 * 24 * 10);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        let stats = subscrypt.get_plan_stats(accounts.alice, 1);
+        assert_eq!(stats.paid_subscriptions, 1);
+        assert_eq!(stats.complimentary_subscriptions, 1);
+        assert_eq!(stats.revenue, 50000);
+    }
+
+    /// Simple scenario that `alice` grants a subscription to `charlie` who has not subscribed
+    /// before, so he can not login until he sets his passes
+    #[ink::test]
+    fn grant_subscription_check_auth_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.grant_subscription(
+            accounts.charlie,
+            1,
+            60 * 60 * 24 * 10,
+            vec!["nothing important".to_string()],
+        );
+        assert!(!subscrypt.check_auth(accounts.charlie, accounts.alice, "".to_string()));
+        assert!(!subscrypt.user_check_auth(accounts.charlie, "".to_string()));
+
+        let mut output = <Sha2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Sha2x256, _>(&"pass_phrase".to_string(), &mut output);
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.subs_crypt_pass_hash_for_each_provider(accounts.alice, output);
+        subscrypt.set_user_subscrypt_pass(output);
+        assert!(subscrypt.check_auth(accounts.charlie, accounts.alice, "pass_phrase".to_string()));
+        assert!(subscrypt.user_check_auth(accounts.charlie, "pass_phrase".to_string()));
+    }
+
+    /// Simple scenario that `charlie` refunds his complimentary subscription so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Complimentary subscriptions can not be refunded!")]
+    fn refund_fails_complimentary() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 10100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.grant_subscription(
+            accounts.charlie,
+            1,
+            60 * 60 * 24 * 10,
+            vec!["nothing important".to_string()],
+        );
+
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.refund(accounts.alice, 1);
+    }
+
+    /// Simple scenario that `alice` imports pre-paid subscriptions of `bob` and `charlie` and
+    /// `bob` renews his imported subscription
+    #[ink::test]
+    fn import_subscriptions_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 100100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.import_subscriptions(
+            vec![
+                ImportedSubscription {
+                    user: accounts.bob,
+                    plan_index: 1,
+                    start_time: 0,
+                    end_time: 60 * 60 * 24 * 10,
+                },
+                ImportedSubscription {
+                    user: accounts.charlie,
+                    plan_index: 0,
+                    start_time: 0,
+                    end_time: 60 * 60 * 24,
+                },
+            ],
+            false,
+        );
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+        assert!(subscrypt.check_subscription(accounts.charlie, accounts.alice, 0));
+        assert_eq!(
+            subscrypt
+                .get_plan_stats(accounts.alice, 1)
+                .paid_subscriptions,
+            1
+        );
+        assert_eq!(subscrypt.get_plan_stats(accounts.alice, 1).revenue, 0);
+
+        // a grandfathering edit does not let the pre-paid record renew for free
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>().unwrap();
+        subscrypt.schedule_plan_edit(
+            1,
+            PlanConsts {
+                duration: 60 * 60 * 24 * 30,
+                price: 60000,
+                max_refund_permille_policy: 100,
+                disabled: false,
+                kind: PlanKind::Recurring,
+            },
+            now + 1,
+            true,
+        );
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+
+        set_caller(callee, accounts.bob, 60000);
+        subscrypt.renew(accounts.alice, 1, vec!["nothing important".to_string()]);
+        let records = subscrypt.retrieve_data_with_wallet(accounts.alice);
+        assert_eq!(records.len(), 2);
+        assert!(!records[0].complimentary);
+        assert_eq!(records[0].plan.duration, 60 * 60 * 24 * 10);
+        assert_eq!(records[1].plan.price, 60000);
+    }
+
+    /// Simple scenario that `alice` imports more than `MAX_IMPORT_BATCH_SIZE` subscriptions in
+    /// one call so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Wrong number of subscriptions!")]
+    fn import_subscriptions_fails_batch_too_large() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.import_subscriptions(
+            vec![
+                ImportedSubscription {
+                    user: accounts.bob,
+                    plan_index: 1,
+                    start_time: 0,
+                    end_time: 60 * 60 * 24 * 10,
+                };
+                MAX_IMPORT_BATCH_SIZE + 1
+            ],
+            true,
+        );
+    }
+
+    /// Simple scenario that `alice` imports a subscription which is started before the contract
+    /// so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Wrong subscription time!")]
+    fn import_subscriptions_fails_start_before_contract() {
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        subscrypt.import_subscriptions(
+            vec![ImportedSubscription {
+                user: accounts.bob,
+                plan_index: 1,
+                start_time: 0,
+                end_time: 60 * 60 * 24 * 10,
+            }],
+            false,
+        );
+    }
+
+    /// Simple scenario that a CSV file of three subscriptions is split into two calls and an
+    /// invalid line is reported
+    #[ink::test]
+    fn chunk_import_csv_works() {
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let bob: String = format!("0x{}", "02".repeat(32));
+        let csv: String = format!(
+            "user,plan_index,start_time,end_time\n{},1,0,100\n{},0,10,200\n\n{},1,20,300\n",
+            bob, bob, bob
+        );
+        let chunks = chunk_import_csv(&csv, 2).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 2);
+        assert_eq!(chunks[1].len(), 1);
+        assert_eq!(
+            chunks[1][0],
+            ImportedSubscription {
+                user: accounts.bob,
+                plan_index: 1,
+                start_time: 20,
+                end_time: 300,
+            }
+        );
+
+        let csv: String = format!("{},1,0,100\n{},x,0,100\n", bob, bob);
+        assert_eq!(
+            chunk_import_csv(&csv, 2),
+            Err("line 2: invalid plan_index".to_string())
+        );
+        assert_eq!(
+            chunk_import_csv(&csv, 0),
+            Err("batch_size can not be 0".to_string())
+        );
+    }
+
+    /// Simple scenario that `bob` disputes his subscription and `alice` settles it by refunding
+    /// part of the frozen amount
+    #[ink::test]
+    fn settle_dispute_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        subscrypt.open_dispute(accounts.alice, 1, 7);
+        let dispute = subscrypt
+            .get_dispute(accounts.bob, accounts.alice, 1)
+            .unwrap();
+        assert_eq!(dispute.frozen_amount, 5000);
+        assert_eq!(dispute.status, DisputeStatus::Open);
+
+        set_caller(callee, accounts.alice, 0);
+        set_account_balance(accounts.alice, 0);
+        subscrypt.settle_dispute(accounts.bob, 1, 5000);
+        assert_eq!(
+            subscrypt
+                .get_dispute(accounts.bob, accounts.alice, 1)
+                .unwrap()
+                .status,
+            DisputeStatus::Settled
+        );
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            5000
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.alice)
+                .expect("Cannot get account balance"),
+            0
+        );
+    }
+
+    /// Simple scenario that `alice` offers less than the refund policy to settle the dispute of
+    /// `bob` so it will fail
+    #[ink::test]
+    #[should_panic(expected = "User amount can not be less than the refund policy!")]
+    fn settle_dispute_fails_below_refund_policy() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        subscrypt.open_dispute(accounts.alice, 1, 7);
+
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.settle_dispute(accounts.bob, 1, 0);
+    }
+
+    /// Simple scenario that `alice` does not settle the dispute of `bob` and `charlie` as the
+    /// arbiter decides it after the response window
+    #[ink::test]
+    fn resolve_dispute_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.set_arbiter(accounts.charlie);
+        subscrypt.set_dispute_windows(1, 60 * 60 * 24);
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.open_dispute(accounts.alice, 1, 7);
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.resolve_dispute(accounts.alice, accounts.bob, 1, 5000);
+        assert_eq!(
+            subscrypt
+                .get_dispute(accounts.bob, accounts.alice, 1)
+                .unwrap()
+                .status,
+            DisputeStatus::Resolved
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            5000
+        );
+    }
+
+    /// Simple scenario that the dispute of `bob` is not closed in its windows so `charlie`
+    /// expires it and `bob` gets the refund of the refund policy
+    #[ink::test]
+    fn expire_dispute_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.set_dispute_windows(1, 1);
+
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.open_dispute(accounts.alice, 1, 7);
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+
+        set_caller(callee, accounts.charlie, 0);
+        subscrypt.expire_dispute(accounts.alice, accounts.bob, 1);
+        assert_eq!(
+            subscrypt
+                .get_dispute(accounts.bob, accounts.alice, 1)
+                .unwrap()
+                .status,
+            DisputeStatus::Expired
+        );
+        assert_eq!(
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(accounts.bob)
+                .expect("Cannot get account balance"),
+            5000
+        );
+    }
+
+    /// Simple scenario that `bob` disputes his subscription and then tries to refund it so it
+    /// will fail
+    #[ink::test]
+    #[should_panic(expected = "This subscription is disputed!")]
+    fn refund_fails_disputed() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        set_account_balance(accounts.bob, 0);
+
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        subscrypt.open_dispute(accounts.alice, 1, 7);
+        subscrypt.refund(accounts.alice, 1);
+    }
+
+    /// Simple scenario that the disputed subscription of `bob` expires before the dispute is
+    /// closed and `bob` tries to subscribe again so it will fail
+    #[ink::test]
+    #[should_panic(expected = "This subscription is disputed!")]
+    fn subscribe_fails_disputed() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![1, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+
+        set_caller(callee, accounts.bob, 10000);
+        subscrypt.subscribe(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+        subscrypt.open_dispute(accounts.alice, 0, 7);
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+        ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+            .expect("Cannot advance block");
+        assert!(!subscrypt.check_subscription(accounts.bob, accounts.alice, 0));
+
+        set_caller(callee, accounts.bob, 10000);
+        subscrypt.subscribe(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["nothing important".to_string()],
+            None,
+        );
+    }
+
+    /// Frozen `DailyLockedAmount` of the storage layout of version 1
+    #[derive(scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+    struct DailyLockedAmountV1 {
+        amount: u128,
+        next_day: u64,
+    }
+
+    /// Frozen root struct of the storage layout of version 1
+    #[derive(SpreadLayout)]
+    struct SubscryptV1 {
+        start_time: u64,
+        provider_register_fee: u128,
+        providers: HashMap<AccountId, ProviderV1>,
+        users: HashMap<AccountId, User>,
+        daily_locked_amounts: HashMap<(AccountId, u64), DailyLockedAmountV1>,
+        records: HashMap<(AccountId, AccountId), PlanRecordV1>,
+        plan_index_to_record_index: HashMap<(AccountId, AccountId, u128), u128>,
+        username_to_address: HashMap<String, AccountId>,
+        address_to_username: HashMap<AccountId, String>,
+    }
+
+    impl SubscryptV1 {
+        fn new(start_time: u64) -> Self {
+            Self {
+                start_time,
+                provider_register_fee: 100,
+                providers: HashMap::new(),
+                users: HashMap::new(),
+                daily_locked_amounts: HashMap::new(),
+                records: HashMap::new(),
+                plan_index_to_record_index: HashMap::new(),
+                username_to_address: HashMap::new(),
+                address_to_username: HashMap::new(),
+            }
+        }
+    }
+
+    /// Root struct of a storage of version 1 after the chain replaced its code and wrote the
+    /// owner of the upgrade
+    #[derive(SpreadLayout)]
+    struct UpgradedV1 {
+        v1: SubscryptV1,
+        storage_version: LazyCell<u32>,
+        upgrade_owner: LazyCell<AccountId>,
+    }
+
+    /// push_upgraded_v1 : writes `v1` and `upgrade_owner` under the root key and loads the
+    /// contract from them like the dispatcher of ink, which never drops it
+    fn push_upgraded_v1(
+        v1: SubscryptV1,
+        upgrade_owner: Option<AccountId>,
+    ) -> ManuallyDrop<Subscrypt> {
+        let root_key = Key::from([0x00; 32]);
+        let upgraded = UpgradedV1 {
+            v1,
+            storage_version: LazyCell::new(None),
+            upgrade_owner: LazyCell::new(upgrade_owner),
+        };
+        push_spread_root(&upgraded, &root_key);
+        ManuallyDrop::new(pull_spread_root(&root_key))
+    }
+
+    /// Simple scenario that a contract of version 1, which has `alice` as a provider and `bob` as
+    /// her user, is upgraded by the chain with `alice` as the owner and she migrates its storage in
+    /// three batches. `charlie` and
+    /// `django` have usernames that are only different in case.
+    #[ink::test]
+    fn migrate_storage_works() {
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 50100);
+        set_caller(callee, accounts.alice, 0);
+        let now: u64 = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get block timestamp");
+
+        // writing the storage of version 1 under the root key
+        let plans: Vec<PlanConstsV1> = vec![
+            PlanConstsV1 {
+                duration: 60 * 60 * 24,
+                price: 10000,
+                max_refund_permille_policy: 50,
+                disabled: false,
+            },
+            PlanConstsV1 {
+                duration: 60 * 60 * 24 * 30,
+                price: 50000,
+                max_refund_permille_policy: 100,
+                disabled: false,
+            },
+        ];
+        let mut v1 = SubscryptV1::new(now);
+        v1.providers.insert(
+            accounts.alice,
+            ProviderV1 {
+                plans: plans.clone(),
+                plans_characteristics: vec![vec!["key".to_string()], vec!["key".to_string()]],
+                money_address: accounts.alice,
+                payment_manager: LinkedList {
+                    head: 30,
+                    back: 30,
+                    length: 1,
+                },
+                subscrypt_pass_hash: [0; 32],
+            },
+        );
+        v1.users.insert(
+            accounts.bob,
+            User {
+                list_of_providers: vec![accounts.alice],
+                subscrypt_pass_hash: [0; 32],
+            },
+        );
+        v1.daily_locked_amounts.insert(
+            (accounts.alice, 30),
+            DailyLockedAmountV1 {
+                amount: 50000,
+                next_day: 30,
+            },
+        );
+        v1.records.insert(
+            (accounts.bob, accounts.alice),
+            PlanRecordV1 {
+                subscription_records: vec![SubscriptionRecordV1 {
+                    provider: accounts.alice,
+                    plan: plans[1],
+                    plan_index: 1,
+                    subscription_time: now,
+                    characteristics_values_encrypted: vec!["nothing important".to_string()],
+                    refunded: false,
+                }],
+                pass_hash: [0; 32],
+            },
+        );
+        v1.plan_index_to_record_index
+            .insert((accounts.bob, accounts.alice, 1), 0);
+        for (username, address) in [
+            ("Alice", accounts.alice),
+            ("bob", accounts.bob),
+            ("Carol", accounts.charlie),
+            ("carol", accounts.django),
+        ] {
+            v1.username_to_address.insert(username.to_string(), address);
+            v1.address_to_username.insert(address, username.to_string());
+        }
+
+        let mut subscrypt = push_upgraded_v1(v1, Some(accounts.alice));
+        assert_eq!(subscrypt.get_storage_version(), 1);
+        assert!(!subscrypt.migrate_storage(3));
+        assert_eq!(subscrypt.configs.owner, accounts.alice);
+        assert_eq!(subscrypt.get_storage_version(), 1);
+        assert_eq!(subscrypt.get_plan_data(accounts.alice, 1).price, 50000);
+        assert!(!subscrypt.migrate_storage(3));
+        assert!(subscrypt.migrate_storage(3));
+        assert_eq!(subscrypt.get_storage_version(), STORAGE_VERSION);
+
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+        let record = subscrypt
+            .subscription_records
+            .get(&(accounts.bob, accounts.alice, 0))
+            .unwrap();
+        assert_eq!(record.periods, 1);
+        assert_eq!(record.bundle_index, None);
+        assert!(!record.complimentary);
+        assert_eq!(
+            subscrypt.get_plan_data(accounts.alice, 1).kind,
+            PlanKind::Recurring
+        );
+        let page = subscrypt.list_providers(None, 0, 10);
+        assert_eq!(page.listings.len(), 1);
+        assert_eq!(page.listings[0].provider, accounts.alice);
+        assert_eq!(page.listings[0].username, "alice".to_string());
+        assert_eq!(
+            subscrypt.get_address_by_username("ALICE".to_string()),
+            accounts.alice
+        );
+        assert_eq!(
+            subscrypt.get_address_by_username("Carol".to_string()),
+            accounts.charlie
+        );
+        assert_eq!(
+            subscrypt.get_address_by_username("carol".to_string()),
+            accounts.django
+        );
+    }
+
+    /// Simple scenario that `alice` migrates a storage of version 1 whose code is replaced without
+    /// writing the owner of the upgrade so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Owner of the upgrade is not set!")]
+    fn migrate_storage_fails_upgrade_owner_not_set() {
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 0);
+        let mut subscrypt = push_upgraded_v1(SubscryptV1::new(0), None);
+        subscrypt.migrate_storage(10);
+    }
+
+    /// Simple scenario that `bob` migrates a storage of version 1 whose owner of the upgrade is
+    /// `alice` so it will fail
+    #[ink::test]
+    #[should_panic(expected = "You are not the owner")]
+    fn migrate_storage_fails_not_owner() {
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.bob, 0);
+        let mut subscrypt = push_upgraded_v1(SubscryptV1::new(0), Some(accounts.alice));
+        subscrypt.migrate_storage(10);
+    }
+
+    /// Simple scenario that the owner migrates storage which is already in the current layout
+    /// so it will fail
+    #[ink::test]
+    #[should_panic(expected = "Storage is already migrated!")]
+    fn migrate_storage_fails_already_migrated() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.migrate_storage(10);
+    }
+
+    /// Simple scenario that `alice` who is the owner upgrades the code
+    #[ink::test]
+    fn set_code_hash_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.alice, 0);
+        subscrypt.set_code_hash(ink_env::Hash::from([1; 32]));
+        assert_eq!(ink_env::test::recorded_events().count(), 1);
+    }
+
+    /// Simple scenario that `bob` who is not the owner wants to upgrade the code so it will fail
+    #[ink::test]
+    #[should_panic(expected = "You are not the owner")]
+    fn set_code_hash_fails_not_owner() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_caller(callee, accounts.bob, 0);
+        subscrypt.set_code_hash(ink_env::Hash::from([1; 32]));
+    }
+
+    /// Simple scenario that `bob` subscribes to two plans of `alice` and renews one of them twice,
+    /// each record is stored by its own index and the last one of each plan is checked
+    #[ink::test]
+    fn subscription_records_indexed_works() {
+        let mut subscrypt = Subscrypt::new();
+        let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+            .expect("Cannot get accounts");
+        let callee =
+            ink_env::test::get_current_contract_account_id::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get contract id");
+        set_account_balance(callee, 200100);
+        set_caller(callee, accounts.alice, 100);
+        subscrypt_provider_register_routine(
+            &mut subscrypt,
+            accounts.alice,
+            vec![60 * 60 * 24, 60 * 60 * 24 * 30],
+            vec![10000, 50000],
+            vec![50, 100],
+            "alice".to_string(),
+            vec![vec!["key".to_string()], vec!["key".to_string()]],
+        );
+        assert_eq!(subscrypt.get_plan_length(accounts.alice), 2);
+
+        set_caller(callee, accounts.bob, 10000);
+        subscrypt.subscribe(
+            accounts.alice,
+            0,
+            [0; 32],
+            "bob".to_string(),
+            vec!["value".to_string()],
+            None,
+        );
+        set_caller(callee, accounts.bob, 50000);
+        subscrypt.subscribe(
+            accounts.alice,
+            1,
+            [0; 32],
+            "bob".to_string(),
+            vec!["value".to_string()],
+            None,
+        );
+        subscrypt.renew(accounts.alice, 1, vec!["value".to_string()]);
+        subscrypt.renew(accounts.alice, 1, vec!["value".to_string()]);
+
+        assert_eq!(
+            subscrypt
+                .records
+                .get(&(accounts.bob, accounts.alice))
+                .unwrap()
+                .record_count,
+            4
+        );
+        for (record_index, plan_index) in [(0, 0), (1, 1), (2, 1), (3, 1)].iter() {
+            assert_eq!(
+                subscrypt
+                    .subscription_records
+                    .get(&(accounts.bob, accounts.alice, *record_index))
+                    .unwrap()
+                    .plan_index,
+                *plan_index
+            );
+        }
+        assert!(subscrypt
+            .subscription_records
+            .get(&(accounts.bob, accounts.alice, 4))
+            .is_none());
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 0));
+        assert!(subscrypt.check_subscription(accounts.bob, accounts.alice, 1));
+        assert_eq!(subscrypt.retrieve_whole_data_with_wallet().len(), 4);
+    }
 }
@@ -5,7 +5,8 @@ pub mod utils {
     use ink_env::AccountId as Account;
     use ink_env::{call, test};
     const DEFAULT_GAS_LIMIT: u64 = 1_000_000;
-    use ink_env::hash::{HashOutput, Sha2x256};
+    use ink_env::hash::{Blake2x256, HashOutput, Sha2x256};
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
 
     /// This function will set the `caller` and `callee` of transaction with endowment amount of
     /// `value`
@@ -50,34 +51,17 @@ pub mod utils {
         );
         for i in 0..durations.len() {
             assert_eq!(
-                subscrypt
-                    .providers
-                    .get(&account)
-                    .unwrap()
-                    .plans
-                    .get(i)
-                    .unwrap()
-                    .duration,
+                subscrypt.plans.get(&(account, i as u128)).unwrap().duration,
                 durations[i]
             );
             assert_eq!(
-                subscrypt
-                    .providers
-                    .get(&account)
-                    .unwrap()
-                    .plans
-                    .get(i)
-                    .unwrap()
-                    .price,
+                subscrypt.plans.get(&(account, i as u128)).unwrap().price,
                 prices[i]
             );
             assert_eq!(
                 subscrypt
-                    .providers
-                    .get(&account)
-                    .unwrap()
                     .plans
-                    .get(i)
+                    .get(&(account, i as u128))
                     .unwrap()
                     .max_refund_permille_policy,
                 max_refund_permille_policies[i]
@@ -100,34 +84,14 @@ pub mod utils {
     ) {
         subscrypt.edit_plan(plan_index, duration, price, max_refund, disabled);
         assert_eq!(
-            subscrypt
-                .providers
-                .get(&account)
-                .unwrap()
-                .plans
-                .get(1)
-                .unwrap()
-                .duration,
+            subscrypt.plans.get(&(account, 1)).unwrap().duration,
             duration
         );
+        assert_eq!(subscrypt.plans.get(&(account, 1)).unwrap().price, price);
         assert_eq!(
             subscrypt
-                .providers
-                .get(&account)
-                .unwrap()
                 .plans
-                .get(1)
-                .unwrap()
-                .price,
-            price
-        );
-        assert_eq!(
-            subscrypt
-                .providers
-                .get(&account)
-                .unwrap()
-                .plans
-                .get(1)
+                .get(&(account, 1))
                 .unwrap()
                 .max_refund_permille_policy,
             max_refund
@@ -153,34 +117,14 @@ pub mod utils {
             plan_charastristics,
         );
         assert_eq!(
-            subscrypt
-                .providers
-                .get(&account)
-                .unwrap()
-                .plans
-                .get(2)
-                .unwrap()
-                .duration,
+            subscrypt.plans.get(&(account, 2)).unwrap().duration,
             durations[0]
         );
+        assert_eq!(subscrypt.plans.get(&(account, 2)).unwrap().price, prices[0]);
         assert_eq!(
             subscrypt
-                .providers
-                .get(&account)
-                .unwrap()
-                .plans
-                .get(2)
-                .unwrap()
-                .price,
-            prices[0]
-        );
-        assert_eq!(
-            subscrypt
-                .providers
-                .get(&account)
-                .unwrap()
                 .plans
-                .get(2)
+                .get(&(account, 2))
                 .unwrap()
                 .max_refund_permille_policy,
             max_refund_permille_policies[0]
@@ -190,4 +134,36 @@ pub mod utils {
             account
         );
     }
+
+    /// This function will return the compressed public key of the meter with `secret_key`
+    pub fn meter_public_key(secret_key: [u8; 32]) -> Vec<u8> {
+        let secret_key = SecretKey::from_slice(&secret_key).expect("Invalid secret key");
+        PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret_key)
+            .serialize()
+            .to_vec()
+    }
+
+    /// This function will sign a usage report by the meter with `secret_key`
+    pub fn sign_usage_report(
+        secret_key: [u8; 32],
+        user: Account,
+        provider: Account,
+        plan_index: u128,
+        units: u128,
+        nonce: u64,
+    ) -> Vec<u8> {
+        let mut message_hash = <Blake2x256 as HashOutput>::Type::default();
+        ink_env::hash_encoded::<Blake2x256, _>(
+            &(user, provider, plan_index, units, nonce),
+            &mut message_hash,
+        );
+        let secret_key = SecretKey::from_slice(&secret_key).expect("Invalid secret key");
+        let message = Message::from_slice(&message_hash).expect("Invalid message hash");
+        let (recovery_id, signature) = Secp256k1::signing_only()
+            .sign_ecdsa_recoverable(&message, &secret_key)
+            .serialize_compact();
+        let mut output = signature.to_vec();
+        output.push(recovery_id.to_i32() as u8);
+        output
+    }
 }